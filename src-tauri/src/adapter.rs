@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use bluest::{Adapter, AdapterEvent};
+use futures_lite::stream::StreamExt;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::error::AppResult;
+use crate::heart;
+
+/// 适配器缺失或事件流结束后重新监听的间隔
+const ADAPTER_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+/// 轮询系统适配器列表的间隔
+#[cfg(target_os = "linux")]
+const ADAPTER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 适配器信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterInfo {
+    pub id: String,
+    /// 被 rfkill 软件禁用
+    pub soft_blocked: bool,
+    /// 被硬件开关禁用
+    pub hard_blocked: bool,
+    /// 是否为系统默认适配器（bluest 只能打开默认适配器，即应用实际使用的适配器）
+    pub default: bool,
+}
+
+/// 适配器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AdapterState {
+    Added,
+    Removed,
+    Available,
+    Unavailable,
+}
+
+/// 适配器状态事件
+#[derive(Debug, Clone, Serialize)]
+pub struct AdapterStateEvent {
+    /// 适配器 ID，默认适配器的开关事件为空
+    pub adapter: Option<String>,
+    pub state: AdapterState,
+    pub timestamp: u64,
+}
+
+/// rfkill 中的蓝牙设备
+#[cfg(target_os = "linux")]
+pub(crate) struct RfkillEntry {
+    pub name: String,
+    pub soft_blocked: bool,
+    pub hard_blocked: bool,
+}
+
+/// 适配器不可用时暂停的心率流
+static PAUSED_STREAMS: Mutex<Vec<String>> = Mutex::const_new(Vec::new());
+
+/// 读取 /sys/class/rfkill 中的蓝牙条目
+#[cfg(target_os = "linux")]
+pub(crate) fn read_bluetooth_rfkill() -> Vec<RfkillEntry> {
+    let read = |path: &std::path::Path, file: &str| {
+        std::fs::read_to_string(path.join(file))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let Ok(entries) = std::fs::read_dir("/sys/class/rfkill") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| read(path, "type") == "bluetooth")
+        .map(|path| RfkillEntry {
+            name: read(&path, "name"),
+            soft_blocked: read(&path, "soft") == "1",
+            hard_blocked: read(&path, "hard") == "1",
+        })
+        .collect()
+}
+
+/// 系统中的适配器 ID
+#[cfg(target_os = "linux")]
+pub(crate) fn system_adapter_ids() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/bluetooth") else {
+        return Vec::new();
+    };
+
+    // 过滤掉 "hci0:70" 这类连接条目
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("hci") && !name.contains(':'))
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// 系统中的适配器 ID（非 Linux 平台只支持默认适配器）
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn system_adapter_ids() -> Vec<String> {
+    vec!["default".to_string()]
+}
+
+/// 系统默认适配器 ID（BlueZ 按名称排序后的第一个适配器）
+fn default_adapter_id() -> Option<String> {
+    system_adapter_ids().into_iter().next()
+}
+
+/// 列出系统中的蓝牙适配器
+#[tauri::command]
+pub async fn list_adapters() -> AppResult<Vec<AdapterInfo>> {
+    let default = default_adapter_id();
+
+    #[cfg(target_os = "linux")]
+    let rfkill = read_bluetooth_rfkill();
+
+    Ok(system_adapter_ids()
+        .into_iter()
+        .map(|id| {
+            #[cfg(target_os = "linux")]
+            let (soft_blocked, hard_blocked) = rfkill
+                .iter()
+                .find(|e| e.name == id)
+                .map(|e| (e.soft_blocked, e.hard_blocked))
+                .unwrap_or_default();
+
+            #[cfg(not(target_os = "linux"))]
+            let (soft_blocked, hard_blocked) = (false, false);
+
+            AdapterInfo {
+                default: default.as_ref() == Some(&id),
+                id,
+                soft_blocked,
+                hard_blocked,
+            }
+        })
+        .collect())
+}
+
+fn emit_adapter_state(app: &AppHandle, adapter: Option<String>, state: AdapterState) {
+    eprintln!("Adapter state ({:?}): {state:?}", adapter);
+    let _ = app.emit(
+        "adapter-state",
+        AdapterStateEvent {
+            adapter,
+            state,
+            timestamp: heart::current_timestamp_millis(),
+        },
+    );
+}
+
+/// 取出暂停中的心率流（不再在适配器恢复后重启），未指定设备时取出全部
+pub(crate) async fn take_paused_streams(device_id: Option<&str>) -> Vec<String> {
+    let mut paused = PAUSED_STREAMS.lock().await;
+    match device_id {
+        Some(id) => {
+            let taken: Vec<String> = paused.iter().filter(|p| *p == id).cloned().collect();
+            paused.retain(|p| p != id);
+            taken
+        }
+        None => std::mem::take(&mut *paused),
+    }
+}
+
+/// 适配器恢复后重新启动被暂停的心率流
+async fn resume_paused_streams(app: &AppHandle) {
+    let paused = std::mem::take(&mut *PAUSED_STREAMS.lock().await);
+    for device_id in paused {
+        eprintln!("Resuming heart rate stream: {device_id}");
+        if let Err(e) = heart::start_heart_rate_stream(app.clone(), Some(device_id.clone()), None).await {
+            eprintln!("Failed to resume heart rate stream ({device_id}): {e}");
+            let _ = app.emit(
+                "heart-rate-error",
+                heart::StreamError {
+                    device_id,
+                    error: e,
+                },
+            );
+        }
+    }
+}
+
+/// 监听默认适配器的开关状态
+async fn watch_default_adapter(app: AppHandle) {
+    loop {
+        if let Some(adapter) = Adapter::default().await {
+            match adapter.events().await {
+                Ok(mut events) => {
+                    while let Some(event) = events.next().await {
+                        match event {
+                            Ok(AdapterEvent::Available) => {
+                                emit_adapter_state(&app, None, AdapterState::Available);
+                                resume_paused_streams(&app).await;
+                            }
+                            Ok(AdapterEvent::Unavailable) => {
+                                emit_adapter_state(&app, None, AdapterState::Unavailable);
+                                // 暂停期间持有暂停列表锁，停止操作会等待暂停完成后再查找
+                                let mut paused = PAUSED_STREAMS.lock().await;
+                                paused.extend(heart::pause_streams(&app).await);
+                            }
+                            Err(e) => {
+                                eprintln!("Adapter event error: {e}");
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to watch adapter events: {e}"),
+            }
+        }
+
+        tokio::time::sleep(ADAPTER_RETRY_INTERVAL).await;
+    }
+}
+
+/// 轮询系统适配器列表，检测热插拔
+#[cfg(target_os = "linux")]
+async fn watch_system_adapters(app: AppHandle) {
+    let mut known = system_adapter_ids();
+    loop {
+        tokio::time::sleep(ADAPTER_POLL_INTERVAL).await;
+
+        let current = system_adapter_ids();
+        for id in current.iter().filter(|id| !known.contains(id)) {
+            emit_adapter_state(&app, Some(id.clone()), AdapterState::Added);
+        }
+        for id in known.iter().filter(|id| !current.contains(id)) {
+            emit_adapter_state(&app, Some(id.clone()), AdapterState::Removed);
+        }
+        known = current;
+    }
+}
+
+/// 启动适配器状态监听
+pub fn init_adapter_watcher(app: &AppHandle) {
+    tauri::async_runtime::spawn(watch_default_adapter(app.clone()));
+
+    #[cfg(target_os = "linux")]
+    tauri::async_runtime::spawn(watch_system_adapters(app.clone()));
+}
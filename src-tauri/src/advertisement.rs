@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use bluest::AdvertisementData;
+use futures_lite::stream::StreamExt;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart::{self, HeartRateMeasurement, HRS_UUID};
+use crate::settings;
+use crate::signal::{self, StaleReason};
+
+// 常量定义
+const HUAMI_COMPANY_ID: u16 = 0x0157;
+const HUAMI_HEART_RATE_OFFSET: usize = 3;
+/// 同一设备两次广播心率之间的最小间隔
+const MIN_EMIT_INTERVAL: Duration = Duration::from_millis(1000);
+/// 检查广播心率是否中断的间隔
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 厂商广播解码器
+struct AdvertisementDecoder {
+    name: &'static str,
+    decode: fn(&AdvertisementData) -> Option<HeartRateMeasurement>,
+}
+
+/// 已支持的广播格式，按顺序尝试
+const DECODERS: &[AdvertisementDecoder] = &[
+    AdvertisementDecoder {
+        name: "huami",
+        decode: decode_huami_advertisement,
+    },
+    AdvertisementDecoder {
+        name: "heart_rate_service_data",
+        decode: decode_service_data_advertisement,
+    },
+];
+
+/// 设备广播状态，用于检测信号中断
+struct BroadcastState {
+    /// 最后一次收到该设备任意广播
+    last_seen: Instant,
+    /// 最后一次收到携带心率的广播
+    last_heart_rate: Instant,
+    last_timestamp: u64,
+    /// 已发送过中断事件，收到新心率前不再重复发送
+    stale: bool,
+}
+
+/// 广播监听任务
+static ADVERTISEMENT_TASK: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::const_new(None);
+
+/// 从广播数据中解析心率，返回解码器名称与测量值
+pub fn decode_advertisement(adv_data: &AdvertisementData) -> Option<(&'static str, HeartRateMeasurement)> {
+    DECODERS
+        .iter()
+        .find_map(|decoder| (decoder.decode)(adv_data).map(|m| (decoder.name, m)))
+}
+
+/// 小米/华米手环：厂商数据（公司 ID 0x0157）第 4 字节为当前心率
+fn decode_huami_advertisement(adv_data: &AdvertisementData) -> Option<HeartRateMeasurement> {
+    let manufacturer_data = adv_data.manufacturer_data.as_ref()?;
+    if manufacturer_data.company_id != HUAMI_COMPANY_ID {
+        return None;
+    }
+    decode_huami_payload(&manufacturer_data.data).map(measurement_from_bpm)
+}
+
+/// 解析华米厂商数据负载，0x00 与 0xFF 表示未测得心率
+fn decode_huami_payload(data: &[u8]) -> Option<u16> {
+    match data.get(HUAMI_HEART_RATE_OFFSET) {
+        Some(&bpm) if bpm != 0x00 && bpm != 0xFF => Some(bpm as u16),
+        _ => None,
+    }
+}
+
+/// 标准格式：服务数据中携带 0x180D 的心率测量值
+fn decode_service_data_advertisement(adv_data: &AdvertisementData) -> Option<HeartRateMeasurement> {
+    let data = adv_data.service_data.get(&HRS_UUID)?;
+    heart::parse_heart_rate(data).ok().filter(|m| m.bpm > 0)
+}
+
+fn measurement_from_bpm(bpm: u16) -> HeartRateMeasurement {
+    HeartRateMeasurement {
+        bpm,
+        sensor_contact_supported: false,
+        sensor_contact_detected: false,
+        energy_expended: None,
+        rr_intervals: Vec::new(),
+        timestamp: heart::current_timestamp_millis(),
+    }
+}
+
+/// 开始广播监听（无需 GATT 连接）
+#[tauri::command]
+pub async fn start_advertisement_listening(app: AppHandle, id: Option<String>) -> AppResult<()> {
+    let mut task = ADVERTISEMENT_TASK.lock().await;
+
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return Err(AppError::new(ErrorCode::InvalidState, "Advertisement listening is already running"));
+    }
+
+    let adapter = heart::get_adapter().await?;
+
+    eprintln!("Starting advertisement listening for device: {:?}", id);
+
+    *task = Some(tokio::task::spawn(async move {
+        let mut scan = match adapter.scan(&[]).await {
+            Ok(scan) => scan,
+            Err(e) => {
+                let error = AppError::from(e);
+                eprintln!("Failed to start advertisement scan: {error}");
+                let _ = app.emit("advertisement-error", error);
+                let _ = app.emit("advertisement-stopped", ());
+                return;
+            }
+        };
+
+        let stale_timeout = settings::load_settings().unwrap_or_default().stale_timeout();
+        let mut stale_check = tokio::time::interval(STALE_CHECK_INTERVAL);
+        let mut last_emitted: HashMap<String, Instant> = HashMap::new();
+        let mut devices: HashMap<String, BroadcastState> = HashMap::new();
+
+        loop {
+            tokio::select! {
+                advertising_device = scan.next() => {
+                    let Some(advertising_device) = advertising_device else {
+                        break;
+                    };
+
+                    let device_id = advertising_device.device.id().to_string();
+                    if id.as_ref().is_some_and(|target| *target != device_id) {
+                        continue;
+                    }
+
+                    let now = Instant::now();
+                    let decoded = decode_advertisement(&advertising_device.adv_data);
+                    if let Some(state) = devices.get_mut(&device_id) {
+                        state.last_seen = now;
+                    }
+
+                    let Some((decoder, measurement)) = decoded else {
+                        continue;
+                    };
+
+                    devices.insert(
+                        device_id.clone(),
+                        BroadcastState {
+                            last_seen: now,
+                            last_heart_rate: now,
+                            last_timestamp: measurement.timestamp,
+                            stale: false,
+                        },
+                    );
+
+                    if last_emitted
+                        .get(&device_id)
+                        .is_some_and(|last| now.duration_since(*last) < MIN_EMIT_INTERVAL)
+                    {
+                        continue;
+                    }
+                    last_emitted.insert(device_id.clone(), now);
+
+                    eprintln!("Advertisement heart rate ({decoder}): {}", measurement.bpm);
+                    heart::emit_heart_rate_sample(&app, &device_id, measurement).await;
+                }
+                _ = stale_check.tick(), if stale_timeout.is_some() => {
+                    let Some(stale_timeout) = stale_timeout else {
+                        continue;
+                    };
+                    for (device_id, state) in devices.iter_mut() {
+                        let silent_for = state.last_heart_rate.elapsed();
+                        if state.stale || silent_for < stale_timeout {
+                            continue;
+                        }
+                        state.stale = true;
+
+                        // 仍能收到广播但不再携带心率，说明手环退出了心率广播模式
+                        let reason = if state.last_seen.elapsed() < stale_timeout {
+                            StaleReason::BroadcastEnded
+                        } else {
+                            StaleReason::LinkLost
+                        };
+                        signal::emit_stale(&app, device_id, reason, Some(state.last_timestamp), silent_for);
+                    }
+                }
+            }
+        }
+
+        eprintln!("Advertisement scan ended");
+        let _ = app.emit("advertisement-stopped", ());
+    }));
+
+    Ok(())
+}
+
+/// 停止广播监听
+#[tauri::command]
+pub async fn stop_advertisement_listening(app: AppHandle) -> AppResult<()> {
+    let mut task = ADVERTISEMENT_TASK.lock().await;
+
+    if let Some(task) = task.take() {
+        task.abort();
+        eprintln!("Advertisement listening stopped");
+        let _ = app.emit("advertisement-stopped", ());
+        Ok(())
+    } else {
+        Err(AppError::new(ErrorCode::InvalidState, "Advertisement listening is not running"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bluest::{AdvertisementData, ManufacturerData};
+
+    use super::*;
+
+    /// 小米手环 (华米) 开启心率广播后的厂商数据负载，第 4 字节为心率 0x48 (72 bpm)
+    const HUAMI_PAYLOAD_72: &[u8] = &[0x02, 0x00, 0x00, 0x48, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// 未测得心率时心率字节为 0xFF
+    const HUAMI_PAYLOAD_NO_READING: &[u8] = &[0x02, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// 心率字节为 0x00 同样表示未测得
+    const HUAMI_PAYLOAD_ZERO: &[u8] = &[0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    /// 标准心率测量值：8 位心率 0x5A (90 bpm)
+    const HRS_SERVICE_DATA_90: &[u8] = &[0x00, 0x5A];
+
+    fn advertisement(
+        manufacturer_data: Option<(u16, &[u8])>,
+        service_data: Option<&[u8]>,
+    ) -> AdvertisementData {
+        let mut services = HashMap::new();
+        if let Some(data) = service_data {
+            services.insert(HRS_UUID, data.to_vec());
+        }
+
+        AdvertisementData {
+            local_name: Some("Mi Smart Band".to_string()),
+            manufacturer_data: manufacturer_data.map(|(company_id, data)| ManufacturerData {
+                company_id,
+                data: data.to_vec(),
+            }),
+            services: Vec::new(),
+            service_data: services,
+            tx_power_level: None,
+            is_connectable: true,
+        }
+    }
+
+    #[test]
+    fn huami_payload_reads_heart_rate_byte() {
+        assert_eq!(decode_huami_payload(HUAMI_PAYLOAD_72), Some(72));
+    }
+
+    #[test]
+    fn huami_payload_rejects_sentinels() {
+        assert_eq!(decode_huami_payload(HUAMI_PAYLOAD_NO_READING), None);
+        assert_eq!(decode_huami_payload(HUAMI_PAYLOAD_ZERO), None);
+    }
+
+    #[test]
+    fn huami_payload_rejects_short_payload() {
+        assert_eq!(decode_huami_payload(&[]), None);
+        assert_eq!(decode_huami_payload(&HUAMI_PAYLOAD_72[..HUAMI_HEART_RATE_OFFSET]), None);
+    }
+
+    #[test]
+    fn decodes_huami_manufacturer_data() {
+        let adv = advertisement(Some((HUAMI_COMPANY_ID, HUAMI_PAYLOAD_72)), None);
+        let (decoder, measurement) = decode_advertisement(&adv).expect("huami advertisement");
+        assert_eq!(decoder, "huami");
+        assert_eq!(measurement.bpm, 72);
+        assert!(measurement.rr_intervals.is_empty());
+    }
+
+    #[test]
+    fn ignores_other_company_id() {
+        let adv = advertisement(Some((0x004C, HUAMI_PAYLOAD_72)), None);
+        assert!(decode_advertisement(&adv).is_none());
+    }
+
+    #[test]
+    fn ignores_huami_without_reading() {
+        let adv = advertisement(Some((HUAMI_COMPANY_ID, HUAMI_PAYLOAD_NO_READING)), None);
+        assert!(decode_advertisement(&adv).is_none());
+    }
+
+    #[test]
+    fn decodes_heart_rate_service_data() {
+        let adv = advertisement(None, Some(HRS_SERVICE_DATA_90));
+        let (decoder, measurement) = decode_advertisement(&adv).expect("service data advertisement");
+        assert_eq!(decoder, "heart_rate_service_data");
+        assert_eq!(measurement.bpm, 90);
+    }
+
+    #[test]
+    fn falls_back_to_service_data_when_huami_has_no_reading() {
+        let adv = advertisement(
+            Some((HUAMI_COMPANY_ID, HUAMI_PAYLOAD_NO_READING)),
+            Some(HRS_SERVICE_DATA_90),
+        );
+        let (decoder, measurement) = decode_advertisement(&adv).expect("service data advertisement");
+        assert_eq!(decoder, "heart_rate_service_data");
+        assert_eq!(measurement.bpm, 90);
+    }
+
+    #[test]
+    fn ignores_invalid_service_data() {
+        assert!(decode_advertisement(&advertisement(None, Some(&[]))).is_none());
+        assert!(decode_advertisement(&advertisement(None, Some(&[0x00, 0x00]))).is_none());
+        // 16 位心率标志但只剩 1 字节
+        assert!(decode_advertisement(&advertisement(None, Some(&[0x01, 0x5A]))).is_none());
+    }
+
+    #[test]
+    fn ignores_empty_advertisement() {
+        assert!(decode_advertisement(&advertisement(None, None)).is_none());
+    }
+}
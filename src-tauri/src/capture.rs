@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use crate::connection::{ConnectionState, ConnectionTracker};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart;
+use crate::settings;
+use crate::source::{HeartRateSource, SourceFuture};
+
+/// 回放设备 ID 前缀
+const REPLAY_DEVICE_PREFIX: &str = "replay:";
+/// 回放倍速范围，过小的倍速会使等待时间溢出
+const MIN_REPLAY_SPEED: f64 = 0.1;
+const MAX_REPLAY_SPEED: f64 = 100.0;
+
+/// 捕获文件中的一条原始通知（JSON Lines 格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CaptureEntry {
+    /// 距离捕获开始的时间 (毫秒)
+    offset_ms: u64,
+    /// 接收时间戳 (Unix 毫秒)
+    timestamp: u64,
+    /// 原始 0x2A37 数据（十六进制）
+    data: String,
+}
+
+/// 正在进行的捕获
+struct CaptureWriter {
+    writer: LineWriter<File>,
+    started: Instant,
+    path: PathBuf,
+}
+
+/// 按设备 ID 索引的捕获
+static CAPTURES: Mutex<BTreeMap<String, CaptureWriter>> = Mutex::const_new(BTreeMap::new());
+
+fn get_captures_dir() -> AppResult<PathBuf> {
+    let dir = settings::get_config_dir()?.join("captures");
+    let _ = fs::create_dir_all(&dir);
+    Ok(dir)
+}
+
+pub(crate) fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> AppResult<Vec<u8>> {
+    let invalid = || AppError::new(ErrorCode::InvalidArgument, format!("Invalid hex data: {value}"));
+    if !value.is_ascii() || !value.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|_| invalid()))
+        .collect()
+}
+
+/// 记录一条原始通知（未开启捕获的设备直接忽略）
+pub(crate) async fn record(device_id: &str, data: &[u8]) {
+    let mut captures = CAPTURES.lock().await;
+    let Some(capture) = captures.get_mut(device_id) else {
+        return;
+    };
+
+    let entry = CaptureEntry {
+        offset_ms: capture.started.elapsed().as_millis() as u64,
+        timestamp: heart::current_timestamp_millis(),
+        data: encode_hex(data),
+    };
+
+    let result = serde_json::to_string(&entry)
+        .map_err(|e| e.to_string())
+        .and_then(|line| writeln!(capture.writer, "{line}").map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Failed to write capture entry: {e}");
+    }
+}
+
+/// 开始捕获设备的原始心率通知，返回捕获文件路径
+#[tauri::command]
+pub async fn start_capture(device_id: Option<String>) -> AppResult<String> {
+    let device_id = heart::resolve_device_id(device_id).await?;
+    let mut captures = CAPTURES.lock().await;
+
+    if let Some(capture) = captures.get(&device_id) {
+        return Err(AppError::new(
+            ErrorCode::InvalidState,
+            format!("Capture already running: {}", capture.path.display()),
+        ));
+    }
+
+    let safe_id: String = device_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = get_captures_dir()?.join(format!("{safe_id}-{}.jsonl", heart::current_timestamp_millis()));
+    let file = File::create(&path)
+        .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("Failed to create capture file: {e}")))?;
+
+    eprintln!("Capturing heart rate notifications to: {:?}", path);
+
+    let display = path.display().to_string();
+    captures.insert(
+        device_id,
+        CaptureWriter {
+            writer: LineWriter::new(file),
+            started: Instant::now(),
+            path,
+        },
+    );
+
+    Ok(display)
+}
+
+/// 停止捕获，返回捕获文件路径
+#[tauri::command]
+pub async fn stop_capture(device_id: Option<String>) -> AppResult<String> {
+    let device_id = heart::resolve_device_id(device_id).await?;
+    let mut capture = CAPTURES
+        .lock()
+        .await
+        .remove(&device_id)
+        .ok_or_else(|| AppError::new(ErrorCode::InvalidState, "No capture is running for this device"))?;
+
+    capture
+        .writer
+        .flush()
+        .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("Failed to flush capture file: {e}")))?;
+
+    eprintln!("Capture stopped: {:?}", capture.path);
+
+    Ok(capture.path.display().to_string())
+}
+
+/// 读取捕获文件
+fn load_capture(path: &Path) -> AppResult<Vec<CaptureEntry>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("Failed to read capture file: {e}")))?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                AppError::new(ErrorCode::InvalidArgument, format!("Invalid capture entry at line {}: {e}", i + 1))
+            })
+        })
+        .collect()
+}
+
+/// 回放倍速，未指定或无效时为实时，超出范围时取边界值
+fn replay_speed(speed: Option<f64>) -> f64 {
+    speed
+        .filter(|s| *s > 0.0)
+        .map_or(1.0, |s| s.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED))
+}
+
+/// 捕获文件回放数据源
+struct ReplaySource {
+    device_id: String,
+    entries: Vec<CaptureEntry>,
+    speed: f64,
+}
+
+impl ReplaySource {
+    /// 按原始时间间隔（除以倍速）重新发送通知，播放完毕后结束
+    async fn replay(self, app: AppHandle, tracker: ConnectionTracker) -> Result<(), Box<dyn Error + Send + Sync>> {
+        tracker.set(ConnectionState::Subscribed).await;
+
+        let started = Instant::now();
+        for entry in self.entries {
+            let due = Duration::from_secs_f64(entry.offset_ms as f64 / 1000.0 / self.speed);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+
+            let data = decode_hex(&entry.data)?;
+            match heart::parse_heart_rate(&data) {
+                Ok(measurement) => {
+                    heart::emit_heart_rate_sample(&app, &self.device_id, measurement).await;
+                }
+                Err(e) => {
+                    eprintln!("Failed to parse replayed heart rate data: {e}");
+                }
+            }
+        }
+
+        eprintln!("Replay finished: {}", self.device_id);
+        Ok(())
+    }
+}
+
+impl HeartRateSource for ReplaySource {
+    fn run(self: Box<Self>, app: AppHandle, tracker: ConnectionTracker) -> SourceFuture {
+        let source = *self;
+        Box::pin(source.replay(app, tracker))
+    }
+
+    fn uses_adapter(&self) -> bool {
+        false
+    }
+}
+
+/// 以虚拟设备回放捕获文件，`speed` 为回放倍速（默认实时，范围 0.1 - 100），返回虚拟设备 ID
+#[tauri::command]
+pub async fn start_replay(
+    app: AppHandle,
+    path: String,
+    speed: Option<f64>,
+    label: Option<String>,
+) -> AppResult<String> {
+    let path = PathBuf::from(path);
+    let entries = load_capture(&path)?;
+    let speed = replay_speed(speed);
+
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let device_id = format!("{REPLAY_DEVICE_PREFIX}{name}");
+
+    eprintln!("Replaying {} notifications from {:?} at {speed}x", entries.len(), path);
+
+    let source = ReplaySource {
+        device_id: device_id.clone(),
+        entries,
+        speed,
+    };
+    heart::start_stream_with_source(app, device_id.clone(), label, Box::new(source)).await?;
+
+    Ok(device_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/captures")
+            .join(name)
+    }
+
+    #[test]
+    fn replays_captured_fixture() {
+        let entries = load_capture(&fixture("mi-band-rest.jsonl")).expect("load fixture");
+        assert_eq!(entries.len(), 5);
+        assert!(entries.windows(2).all(|pair| pair[0].offset_ms < pair[1].offset_ms));
+
+        let measurements: Vec<_> = entries
+            .iter()
+            .map(|entry| heart::parse_heart_rate(&decode_hex(&entry.data).unwrap()).unwrap())
+            .collect();
+
+        let bpm: Vec<u16> = measurements.iter().map(|m| m.bpm).collect();
+        assert_eq!(bpm, [72, 74, 73, 75, 80]);
+
+        assert!(measurements[0].sensor_contact_supported && measurements[0].sensor_contact_detected);
+        assert_eq!(measurements[0].rr_intervals, [853.0 * 1000.0 / 1024.0]);
+        assert!(measurements[2].sensor_contact_supported && !measurements[2].sensor_contact_detected);
+        assert_eq!(measurements[3].energy_expended, Some(16));
+        assert_eq!(measurements[3].rr_intervals, [832.0 * 1000.0 / 1024.0]);
+        assert!(!measurements[4].sensor_contact_supported);
+    }
+
+    #[test]
+    fn rejects_missing_capture_file() {
+        let error = load_capture(&fixture("missing.jsonl")).unwrap_err();
+        assert_eq!(error.code, ErrorCode::StorageIo);
+    }
+
+    #[test]
+    fn clamps_replay_speed() {
+        assert_eq!(replay_speed(None), 1.0);
+        assert_eq!(replay_speed(Some(-1.0)), 1.0);
+        assert_eq!(replay_speed(Some(f64::NAN)), 1.0);
+        assert_eq!(replay_speed(Some(1e-18)), MIN_REPLAY_SPEED);
+        assert_eq!(replay_speed(Some(f64::INFINITY)), MAX_REPLAY_SPEED);
+        assert_eq!(replay_speed(Some(2.0)), 2.0);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(decode_hex(&encode_hex(&[0x16, 0x48, 0x55, 0x03])).unwrap(), [0x16, 0x48, 0x55, 0x03]);
+        assert!(decode_hex("164").is_err());
+        assert!(decode_hex("zz").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+
+use crate::error::AppResult;
+use crate::heart;
+
+/// 连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConnectionState {
+    Idle,
+    Scanning,
+    Connecting,
+    DiscoveringServices,
+    Subscribed,
+    Reconnecting,
+    Failed,
+    Stopped,
+}
+
+/// 连接状态快照
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSnapshot {
+    pub device_id: String,
+    pub state: ConnectionState,
+    /// 当前重连次数，首次连接为 0
+    pub attempt: u32,
+    pub last_error: Option<String>,
+    pub timestamp: u64,
+}
+
+impl ConnectionSnapshot {
+    fn idle(device_id: String) -> Self {
+        Self {
+            device_id,
+            state: ConnectionState::Idle,
+            attempt: 0,
+            last_error: None,
+            timestamp: heart::current_timestamp_millis(),
+        }
+    }
+}
+
+/// 按设备 ID 索引的连接状态，与心率流状态分开加锁
+static CONNECTION_STATES: RwLock<BTreeMap<String, ConnectionSnapshot>> = RwLock::const_new(BTreeMap::new());
+
+/// 单个心率流的连接状态跟踪器
+pub(crate) struct ConnectionTracker {
+    app: AppHandle,
+    device_id: String,
+    attempt: u32,
+    last_error: Option<String>,
+}
+
+impl ConnectionTracker {
+    pub(crate) fn new(app: AppHandle, device_id: String) -> Self {
+        Self {
+            app,
+            device_id,
+            attempt: 0,
+            last_error: None,
+        }
+    }
+
+    pub(crate) fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// 记录一次失败，进入下一次重连尝试
+    pub(crate) fn record_error(&mut self, error: String) {
+        self.attempt += 1;
+        self.last_error = Some(error);
+    }
+
+    /// 连接成功后重置重连计数
+    pub(crate) fn reset_attempts(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// 切换状态并广播 "connection-state" 事件
+    pub(crate) async fn set(&self, state: ConnectionState) {
+        set_connection_state(
+            &self.app,
+            &self.device_id,
+            state,
+            self.attempt,
+            self.last_error.clone(),
+        )
+        .await;
+    }
+}
+
+/// 更新设备连接状态并广播
+pub(crate) async fn set_connection_state(
+    app: &AppHandle,
+    device_id: &str,
+    state: ConnectionState,
+    attempt: u32,
+    last_error: Option<String>,
+) {
+    let snapshot = ConnectionSnapshot {
+        device_id: device_id.to_string(),
+        state,
+        attempt,
+        last_error,
+        timestamp: heart::current_timestamp_millis(),
+    };
+
+    eprintln!("Connection state ({device_id}): {state:?} (attempt {attempt})");
+
+    CONNECTION_STATES
+        .write()
+        .await
+        .insert(device_id.to_string(), snapshot.clone());
+    let _ = app.emit("connection-state", snapshot);
+}
+
+/// 停止时保留最后的错误与重连次数，仅切换状态
+pub(crate) async fn mark_stopped(app: &AppHandle, device_id: &str) {
+    let previous = CONNECTION_STATES.read().await.get(device_id).cloned();
+    let (attempt, last_error) = previous
+        .map(|s| (s.attempt, s.last_error))
+        .unwrap_or_default();
+    set_connection_state(app, device_id, ConnectionState::Stopped, attempt, last_error).await;
+}
+
+/// 失败时保留重连次数，记录最终错误
+pub(crate) async fn mark_failed(app: &AppHandle, device_id: &str, error: String) {
+    let attempt = CONNECTION_STATES
+        .read()
+        .await
+        .get(device_id)
+        .map(|s| s.attempt)
+        .unwrap_or_default();
+    set_connection_state(app, device_id, ConnectionState::Failed, attempt, Some(error)).await;
+}
+
+/// 获取设备当前连接状态，未指定设备时使用选中的设备
+#[tauri::command]
+pub async fn get_connection_state(device_id: Option<String>) -> AppResult<ConnectionSnapshot> {
+    let device_id = heart::resolve_device_id(device_id).await?;
+    let states = CONNECTION_STATES.read().await;
+    Ok(states
+        .get(&device_id)
+        .cloned()
+        .unwrap_or_else(|| ConnectionSnapshot::idle(device_id)))
+}
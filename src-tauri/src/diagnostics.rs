@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use bluest::error::ErrorKind;
+use bluest::Adapter;
+use serde::Serialize;
+use tokio::time::timeout;
+
+use crate::adapter;
+use crate::error::{AppResult, ErrorCode};
+use crate::heart;
+
+/// 等待适配器可用的超时，超时视为适配器未开启
+const POWER_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+/// D-Bus 系统总线套接字
+#[cfg(target_os = "linux")]
+const DBUS_SYSTEM_SOCKET: &str = "/run/dbus/system_bus_socket";
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CheckStatus {
+    Pass,
+    Warning,
+    Fail,
+    /// 前置检查失败，无法执行
+    Skipped,
+}
+
+/// 单项检查
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    /// 检查项标识，保持稳定
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// 对应的错误码，通过时为空
+    pub code: Option<ErrorCode>,
+    /// 建议的修复方法
+    pub fix: Option<&'static str>,
+}
+
+impl DiagnosticCheck {
+    fn pass(id: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            code: None,
+            fix: None,
+        }
+    }
+
+    fn fail(id: &'static str, code: ErrorCode, detail: impl Into<String>, fix: &'static str) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            code: Some(code),
+            fix: Some(fix),
+        }
+    }
+
+    fn warning(id: &'static str, code: ErrorCode, detail: impl Into<String>, fix: &'static str) -> Self {
+        Self {
+            status: CheckStatus::Warning,
+            ..Self::fail(id, code, detail, fix)
+        }
+    }
+
+    fn skipped(id: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Skipped,
+            detail: detail.into(),
+            code: None,
+            fix: None,
+        }
+    }
+}
+
+/// 蓝牙环境诊断报告
+#[derive(Debug, Clone, Serialize)]
+pub struct BluetoothDiagnostics {
+    pub checks: Vec<DiagnosticCheck>,
+    /// 所有检查均未失败
+    pub healthy: bool,
+    pub timestamp: u64,
+}
+
+/// BlueZ 守护进程是否在运行
+#[cfg(target_os = "linux")]
+fn check_bluez() -> DiagnosticCheck {
+    let running = std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                std::fs::read_to_string(entry.path().join("comm"))
+                    .is_ok_and(|comm| comm.trim() == "bluetoothd")
+            })
+        })
+        .unwrap_or(false);
+
+    if running {
+        DiagnosticCheck::pass("bluez", "bluetoothd is running")
+    } else {
+        DiagnosticCheck::fail(
+            "bluez",
+            ErrorCode::AdapterMissing,
+            "bluetoothd is not running",
+            "Install BlueZ and start it with `sudo systemctl enable --now bluetooth`.",
+        )
+    }
+}
+
+/// D-Bus 系统总线是否存在
+#[cfg(target_os = "linux")]
+fn check_dbus_socket() -> DiagnosticCheck {
+    if std::path::Path::new(DBUS_SYSTEM_SOCKET).exists() {
+        DiagnosticCheck::pass("dbus", format!("System bus found at {DBUS_SYSTEM_SOCKET}"))
+    } else {
+        DiagnosticCheck::fail(
+            "dbus",
+            ErrorCode::PermissionDenied,
+            format!("System bus socket {DBUS_SYSTEM_SOCKET} not found"),
+            "Start the D-Bus system daemon; inside Flatpak or containers, allow access to the system bus (org.bluez).",
+        )
+    }
+}
+
+/// 系统中是否有蓝牙适配器
+#[cfg(target_os = "linux")]
+fn check_adapter_present() -> DiagnosticCheck {
+    let ids = adapter::system_adapter_ids();
+    if ids.is_empty() {
+        DiagnosticCheck::fail(
+            "adapter_present",
+            ErrorCode::AdapterMissing,
+            "No adapter in /sys/class/bluetooth",
+            "Plug in a Bluetooth adapter and check `lsmod | grep btusb` and `dmesg` for driver errors.",
+        )
+    } else {
+        DiagnosticCheck::pass(
+            "adapter_present",
+            format!("Found adapters: {} (in use: {})", ids.join(", "), ids[0]),
+        )
+    }
+}
+
+/// 适配器是否被 rfkill 禁用
+#[cfg(target_os = "linux")]
+fn check_rfkill() -> DiagnosticCheck {
+    let entries = adapter::read_bluetooth_rfkill();
+    if entries.is_empty() {
+        return DiagnosticCheck::skipped("rfkill", "No Bluetooth entries in /sys/class/rfkill");
+    }
+
+    let hard: Vec<&str> = entries.iter().filter(|e| e.hard_blocked).map(|e| e.name.as_str()).collect();
+    let soft: Vec<&str> = entries.iter().filter(|e| e.soft_blocked).map(|e| e.name.as_str()).collect();
+
+    if !hard.is_empty() {
+        DiagnosticCheck::fail(
+            "rfkill",
+            ErrorCode::AdapterOff,
+            format!("Hard blocked: {}", hard.join(", ")),
+            "Turn on the hardware wireless switch or disable airplane mode in the firmware settings.",
+        )
+    } else if !soft.is_empty() {
+        DiagnosticCheck::fail(
+            "rfkill",
+            ErrorCode::AdapterOff,
+            format!("Soft blocked: {}", soft.join(", ")),
+            "Run `rfkill unblock bluetooth`.",
+        )
+    } else {
+        DiagnosticCheck::pass("rfkill", "Not blocked")
+    }
+}
+
+/// 能否通过蓝牙栈打开适配器，并且适配器已开启。
+/// 系统中没有适配器时跳过；只有实际遇到授权错误时才报告权限问题
+async fn check_adapter_access(checks: &mut Vec<DiagnosticCheck>) {
+    let adapter_missing = checks
+        .iter()
+        .any(|c| c.id == "adapter_present" && c.status == CheckStatus::Fail);
+    if adapter_missing {
+        checks.push(DiagnosticCheck::skipped("adapter_access", "No adapter present"));
+        checks.push(DiagnosticCheck::skipped("adapter_powered", "No adapter present"));
+        return;
+    }
+
+    // bluest 不区分"没有适配器"与"无权访问"，打不开时只能报告适配器缺失
+    let Some(adapter) = Adapter::default().await else {
+        checks.push(DiagnosticCheck::fail(
+            "adapter_access",
+            ErrorCode::AdapterMissing,
+            "The Bluetooth stack did not return a default adapter",
+            "Make sure the Bluetooth service is running and the adapter is registered with it (`bluetoothctl list` on Linux).",
+        ));
+        checks.push(DiagnosticCheck::skipped("adapter_powered", "Adapter could not be opened"));
+        return;
+    };
+
+    checks.push(DiagnosticCheck::pass("adapter_access", "Default adapter opened"));
+
+    let powered = match timeout(POWER_CHECK_TIMEOUT, adapter.wait_available()).await {
+        Ok(Ok(_)) => DiagnosticCheck::pass("adapter_powered", "Adapter is powered on"),
+        Ok(Err(e)) if matches!(e.kind(), ErrorKind::NotAuthorized) => DiagnosticCheck::fail(
+            "adapter_powered",
+            ErrorCode::PermissionDenied,
+            e.to_string(),
+            "Grant Bluetooth access to the app, or add your user to the `bluetooth` group and log in again.",
+        ),
+        Ok(Err(e)) => DiagnosticCheck::fail(
+            "adapter_powered",
+            ErrorCode::AdapterOff,
+            e.to_string(),
+            "Power on the adapter with `bluetoothctl power on` or from the system Bluetooth settings.",
+        ),
+        Err(_) => DiagnosticCheck::fail(
+            "adapter_powered",
+            ErrorCode::AdapterOff,
+            "Adapter did not become available",
+            "Power on the adapter with `bluetoothctl power on` or from the system Bluetooth settings.",
+        ),
+    };
+    checks.push(powered);
+}
+
+/// 逐项检查蓝牙环境（BlueZ、D-Bus、rfkill、适配器与电源状态），返回检查清单与修复建议
+#[tauri::command]
+pub async fn bluetooth_diagnostics() -> AppResult<BluetoothDiagnostics> {
+    let mut checks = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    {
+        checks.push(check_bluez());
+        checks.push(check_dbus_socket());
+        checks.push(check_adapter_present());
+        checks.push(check_rfkill());
+    }
+
+    check_adapter_access(&mut checks).await;
+
+    for check in checks.iter().filter(|c| c.status != CheckStatus::Pass) {
+        eprintln!("Bluetooth diagnostics: {} {:?} - {}", check.id, check.status, check.detail);
+    }
+
+    Ok(BluetoothDiagnostics {
+        healthy: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+        timestamp: heart::current_timestamp_millis(),
+    })
+}
@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::fmt;
+
+use bluest::error::ErrorKind;
+use serde::Serialize;
+
+/// 错误码，前端据此显示本地化提示，取值保持稳定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ErrorCode {
+    /// 未找到蓝牙适配器
+    AdapterMissing,
+    /// 蓝牙适配器已关闭或被禁用
+    AdapterOff,
+    /// 未找到设备
+    DeviceNotFound,
+    /// 设备不支持所需的服务或特征
+    ServiceMissing,
+    /// 订阅通知超时
+    SubscribeTimeout,
+    /// 没有访问蓝牙的权限
+    PermissionDenied,
+    /// 读写设置文件失败
+    SettingsIo,
+    /// 读写数据文件失败
+    StorageIo,
+    /// 连接设备失败
+    ConnectionFailed,
+    /// 数据流中断且重连失败
+    ConnectionLost,
+    /// 参数无效
+    InvalidArgument,
+    /// 当前状态不允许该操作（如重复启动）
+    InvalidState,
+    /// 其他蓝牙错误
+    Bluetooth,
+}
+
+impl ErrorCode {
+    /// 解决建议
+    pub fn hint(self) -> &'static str {
+        match self {
+            Self::AdapterMissing => "Plug in a Bluetooth adapter and make sure its driver is loaded.",
+            Self::AdapterOff => "Turn Bluetooth on, and unblock it with `rfkill unblock bluetooth` if it is blocked.",
+            Self::DeviceNotFound => "Wake the band, keep it close to the computer and make sure heart rate broadcast is enabled.",
+            Self::ServiceMissing => "Enable heart rate broadcast on the band; the device may not expose the standard heart rate service.",
+            Self::SubscribeTimeout => "Remove the pairing in the system Bluetooth settings and connect again.",
+            Self::PermissionDenied => "Grant Bluetooth access to the app, or add your user to the `bluetooth` group on Linux.",
+            Self::SettingsIo => "Check that the config directory exists and is writable.",
+            Self::StorageIo => "Check that the file exists and the config directory is writable.",
+            Self::ConnectionFailed => "Make sure the band is not connected to another phone or app, then try again.",
+            Self::ConnectionLost => "Keep the band within range; the stream stops after the configured reconnect attempts.",
+            Self::InvalidArgument => "Check the value and try again.",
+            Self::InvalidState => "Wait for the current operation to finish or stop it first.",
+            Self::Bluetooth => "Toggle Bluetooth off and on, then try again.",
+        }
+    }
+}
+
+/// 返回给前端的错误
+#[derive(Debug, Clone, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    /// 原始错误信息，用于日志与调试
+    pub message: String,
+    pub hint: &'static str,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            hint: code.hint(),
+        }
+    }
+
+    /// 从数据源返回的错误还原，无法识别时归为 `fallback`
+    pub fn from_boxed(error: Box<dyn Error + Send + Sync>, fallback: ErrorCode) -> Self {
+        match error.downcast::<AppError>() {
+            Ok(error) => *error,
+            Err(error) => match error.downcast::<bluest::Error>() {
+                Ok(error) => (*error).into(),
+                Err(error) => Self::new(fallback, error.to_string()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl Error for AppError {}
+
+impl From<bluest::Error> for AppError {
+    fn from(error: bluest::Error) -> Self {
+        let code = match error.kind() {
+            ErrorKind::AdapterUnavailable => ErrorCode::AdapterOff,
+            ErrorKind::NotAuthorized => ErrorCode::PermissionDenied,
+            ErrorKind::NotFound => ErrorCode::DeviceNotFound,
+            ErrorKind::NotConnected => ErrorCode::ConnectionFailed,
+            _ => ErrorCode::Bluetooth,
+        };
+        Self::new(code, error.to_string())
+    }
+}
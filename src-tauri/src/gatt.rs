@@ -0,0 +1,249 @@
+use std::fs;
+use std::time::Duration;
+
+use bluest::btuuid::bluetooth_uuid_from_u16;
+use bluest::{Characteristic, Service, Uuid};
+use serde::Serialize;
+use tokio::time::timeout;
+
+use crate::capture;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart::{self, DeviceInfo};
+use crate::settings;
+
+/// 单次发现或读取操作的超时
+const GATT_OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 常见的 16 位服务 UUID 名称
+const SERVICE_NAMES: &[(u16, &str)] = &[
+    (0x1800, "Generic Access"),
+    (0x1801, "Generic Attribute"),
+    (0x1805, "Current Time"),
+    (0x180A, "Device Information"),
+    (0x180D, "Heart Rate"),
+    (0x180F, "Battery"),
+    (0x1812, "Human Interface Device"),
+    (0x1814, "Running Speed and Cadence"),
+    (0x1816, "Cycling Speed and Cadence"),
+    (0x1818, "Cycling Power"),
+    (0x181C, "User Data"),
+    (0xFE95, "Xiaomi"),
+    (0xFEE0, "Huami (Mi Band)"),
+    (0xFEE1, "Huami Authentication"),
+];
+
+/// 常见的 16 位特征 UUID 名称
+const CHARACTERISTIC_NAMES: &[(u16, &str)] = &[
+    (0x2A00, "Device Name"),
+    (0x2A01, "Appearance"),
+    (0x2A04, "Peripheral Preferred Connection Parameters"),
+    (0x2A05, "Service Changed"),
+    (0x2A19, "Battery Level"),
+    (0x2A23, "System ID"),
+    (0x2A24, "Model Number String"),
+    (0x2A25, "Serial Number String"),
+    (0x2A26, "Firmware Revision String"),
+    (0x2A27, "Hardware Revision String"),
+    (0x2A28, "Software Revision String"),
+    (0x2A29, "Manufacturer Name String"),
+    (0x2A2A, "IEEE 11073-20601 Regulatory Certification Data List"),
+    (0x2A2B, "Current Time"),
+    (0x2A37, "Heart Rate Measurement"),
+    (0x2A38, "Body Sensor Location"),
+    (0x2A39, "Heart Rate Control Point"),
+    (0x2A50, "PnP ID"),
+    (0x2AA6, "Central Address Resolution"),
+];
+
+/// 特征属性
+#[derive(Debug, Clone, Serialize)]
+pub struct GattProperties {
+    pub broadcast: bool,
+    pub read: bool,
+    pub write_without_response: bool,
+    pub write: bool,
+    pub notify: bool,
+    pub indicate: bool,
+    pub authenticated_signed_writes: bool,
+    pub extended_properties: bool,
+}
+
+/// 特征信息
+#[derive(Debug, Clone, Serialize)]
+pub struct GattCharacteristic {
+    pub uuid: String,
+    /// 已知 UUID 的名称
+    pub name: Option<&'static str>,
+    pub properties: Option<GattProperties>,
+    /// 读取到的原始值（十六进制）
+    pub value_hex: Option<String>,
+    /// 值为可打印 UTF-8 文本时的内容
+    pub value_text: Option<String>,
+    /// 读取失败的原因
+    pub read_error: Option<String>,
+}
+
+/// 服务信息
+#[derive(Debug, Clone, Serialize)]
+pub struct GattService {
+    pub uuid: String,
+    pub name: Option<&'static str>,
+    pub characteristics: Vec<GattCharacteristic>,
+    /// 特征发现失败的原因
+    pub error: Option<String>,
+}
+
+/// 设备 GATT 结构报告
+#[derive(Debug, Clone, Serialize)]
+pub struct GattReport {
+    pub device: DeviceInfo,
+    pub services: Vec<GattService>,
+    pub timestamp: u64,
+    /// 导出的 JSON 文件路径
+    pub export_path: Option<String>,
+}
+
+/// 提取 16 位 SIG UUID，非标准 UUID 返回 None
+fn short_uuid(uuid: Uuid) -> Option<u16> {
+    let value = uuid.as_u128();
+    if value & !(0xFFFF_FFFFu128 << 96) != bluetooth_uuid_from_u16(0).as_u128() {
+        return None;
+    }
+    u16::try_from(value >> 96).ok()
+}
+
+fn lookup_name(table: &'static [(u16, &'static str)], uuid: Uuid) -> Option<&'static str> {
+    let short = short_uuid(uuid)?;
+    table.iter().find(|(id, _)| *id == short).map(|(_, name)| *name)
+}
+
+/// 可打印文本才作为字符串展示
+fn printable_text(data: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(data).ok()?.trim_end_matches('\0');
+    (!text.is_empty() && text.chars().all(|c| !c.is_control() || c.is_whitespace())).then(|| text.to_string())
+}
+
+async fn inspect_characteristic(characteristic: &Characteristic) -> GattCharacteristic {
+    let uuid = characteristic.uuid();
+    let mut result = GattCharacteristic {
+        uuid: uuid.to_string(),
+        name: lookup_name(CHARACTERISTIC_NAMES, uuid),
+        properties: None,
+        value_hex: None,
+        value_text: None,
+        read_error: None,
+    };
+
+    let properties = match characteristic.properties().await {
+        Ok(properties) => properties,
+        Err(e) => {
+            result.read_error = Some(format!("Failed to read properties: {e}"));
+            return result;
+        }
+    };
+
+    result.properties = Some(GattProperties {
+        broadcast: properties.broadcast,
+        read: properties.read,
+        write_without_response: properties.write_without_response,
+        write: properties.write,
+        notify: properties.notify,
+        indicate: properties.indicate,
+        authenticated_signed_writes: properties.authenticated_signed_writes,
+        extended_properties: properties.extended_properties,
+    });
+
+    if !properties.read {
+        return result;
+    }
+
+    match timeout(GATT_OPERATION_TIMEOUT, characteristic.read()).await {
+        Ok(Ok(data)) => {
+            result.value_text = printable_text(&data);
+            result.value_hex = Some(capture::encode_hex(&data));
+        }
+        Ok(Err(e)) => result.read_error = Some(e.to_string()),
+        Err(_) => result.read_error = Some("Read timeout".to_string()),
+    }
+
+    result
+}
+
+async fn inspect_service(service: &Service) -> GattService {
+    let uuid = service.uuid();
+    let mut result = GattService {
+        uuid: uuid.to_string(),
+        name: lookup_name(SERVICE_NAMES, uuid),
+        characteristics: Vec::new(),
+        error: None,
+    };
+
+    match timeout(GATT_OPERATION_TIMEOUT, service.discover_characteristics()).await {
+        Ok(Ok(characteristics)) => {
+            for characteristic in &characteristics {
+                result.characteristics.push(inspect_characteristic(characteristic).await);
+            }
+        }
+        Ok(Err(e)) => result.error = Some(e.to_string()),
+        Err(_) => result.error = Some("Characteristic discovery timeout".to_string()),
+    }
+
+    result
+}
+
+/// 导出报告到配置目录下的 inspections 目录
+fn export_report(report: &GattReport) -> AppResult<String> {
+    let dir = settings::get_config_dir()?.join("inspections");
+    let _ = fs::create_dir_all(&dir);
+
+    let safe_id: String = report
+        .device
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{safe_id}-{}.json", report.timestamp));
+
+    let content = serde_json::to_string_pretty(report)
+        .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("Failed to serialize report: {e}")))?;
+    fs::write(&path, content)
+        .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("Failed to write report: {e}")))?;
+
+    eprintln!("GATT report exported to: {:?}", path);
+    Ok(path.display().to_string())
+}
+
+/// 连接设备并列出全部服务与特征（含可读的值），用于排查未适配的设备。
+/// `export` 为 true 时同时导出为 JSON 文件
+#[tauri::command]
+pub async fn inspect_device(id: Option<String>, export: Option<bool>) -> AppResult<GattReport> {
+    let device_id = heart::resolve_device_id(id).await?;
+
+    let adapter = heart::get_adapter().await?;
+    let device = heart::find_heart_rate_device(&adapter, Some(&device_id)).await?;
+    heart::ensure_connected(&adapter, &device).await?;
+
+    eprintln!("Inspecting GATT services of device: {device_id}");
+
+    let services = timeout(GATT_OPERATION_TIMEOUT, device.discover_services())
+        .await
+        .map_err(|_| AppError::new(ErrorCode::ConnectionFailed, "Service discovery timeout"))??;
+
+    let mut report = GattReport {
+        device: heart::device_to_info(&device, device.is_connected().await).await,
+        services: Vec::new(),
+        timestamp: heart::current_timestamp_millis(),
+        export_path: None,
+    };
+    for service in &services {
+        report.services.push(inspect_service(service).await);
+    }
+
+    eprintln!("Found {} services", report.services.len());
+
+    if export.unwrap_or(false) {
+        report.export_path = Some(export_report(&report)?);
+    }
+
+    Ok(report)
+}
@@ -1,20 +1,50 @@
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::time::{Duration, Instant};
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use bluest::{btuuid::bluetooth_uuid_from_u16, Adapter, Device, Uuid};
+use bluest::{btuuid::bluetooth_uuid_from_u16, Adapter, AdvertisingDevice, Device, Uuid};
 use futures_lite::stream::StreamExt;
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
 use tokio::time::timeout;
 
+use crate::adapter;
+use crate::capture;
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::connection::{self, ConnectionState, ConnectionTracker};
+use crate::history;
+use crate::hrv;
+use crate::known_devices;
+use crate::scan::DeviceFilter;
+use crate::settings::{self, ScanFilter};
+use crate::simulation::{self, SimulatedSource, SimulationProfile};
+use crate::source::{HeartRateSource, SourceFuture};
+use crate::signal::{self, NotificationStats, StaleReason, SIGNAL_SAMPLE_INTERVAL};
+use crate::summary::{self, SessionSummary};
+use crate::zone;
+
 // 常量定义
-const HRS_UUID: Uuid = bluetooth_uuid_from_u16(0x180D);
+pub(crate) const HRS_UUID: Uuid = bluetooth_uuid_from_u16(0x180D);
 const HRM_UUID: Uuid = bluetooth_uuid_from_u16(0x2A37);
+const HR_CONTROL_POINT_UUID: Uuid = bluetooth_uuid_from_u16(0x2A39);
+const HR_CONTROL_POINT_RESET_ENERGY: u8 = 0x01;
+const BODY_SENSOR_LOCATION_UUID: Uuid = bluetooth_uuid_from_u16(0x2A38);
+const DEVICE_INFORMATION_UUID: Uuid = bluetooth_uuid_from_u16(0x180A);
+const MANUFACTURER_NAME_UUID: Uuid = bluetooth_uuid_from_u16(0x2A29);
+const MODEL_NUMBER_UUID: Uuid = bluetooth_uuid_from_u16(0x2A24);
+const SERIAL_NUMBER_UUID: Uuid = bluetooth_uuid_from_u16(0x2A25);
+const HARDWARE_REVISION_UUID: Uuid = bluetooth_uuid_from_u16(0x2A27);
+const FIRMWARE_REVISION_UUID: Uuid = bluetooth_uuid_from_u16(0x2A26);
+const BATTERY_SERVICE_UUID: Uuid = bluetooth_uuid_from_u16(0x180F);
+const BATTERY_LEVEL_UUID: Uuid = bluetooth_uuid_from_u16(0x2A19);
 const SCAN_TIMEOUT: Duration = Duration::from_secs(5);
+/// 等待设备出现时两次扫描之间的间隔
+const DEVICE_WAIT_INTERVAL: Duration = Duration::from_secs(5);
 const SCAN_INTERVAL: Duration = Duration::from_millis(100);
 const _DEVICE_TIMEOUT: Duration = Duration::from_millis(500);
-const MAX_DEVICES: usize = 100;
 const MAC_ADDRESS_LENGTH: usize = 12;
 
 // Linux D-Bus 优化常量
@@ -22,6 +52,8 @@ const DBUS_OPERATION_TIMEOUT: Duration = Duration::from_secs(30);
 const OPERATION_DELAY: Duration = Duration::from_millis(300);
 const MAX_RETRIES: u32 = 5;
 const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+/// 相邻样本间隔超过该值 (毫秒) 视为数据中断（如断线重连）
+pub(crate) const MAX_SAMPLE_GAP_MS: u64 = 5_000;
 
 /// 设备信息
 #[derive(Debug, Clone, Serialize)]
@@ -30,23 +62,158 @@ pub struct DeviceInfo {
     pub mac_address: Option<String>,
     pub name: Option<String>,
     pub connected: bool,
+    /// 信号强度 (dBm)
+    pub rssi: Option<i16>,
+}
+
+/// 设备详情 (Device Information Service 0x180A)
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceDetails {
+    #[serde(flatten)]
+    pub info: DeviceInfo,
+    pub manufacturer: Option<String>,
+    pub model_number: Option<String>,
+    pub serial_number: Option<String>,
+    pub firmware_revision: Option<String>,
+    pub hardware_revision: Option<String>,
+    pub body_sensor_location: Option<BodySensorLocation>,
+}
+
+/// 传感器佩戴位置 (Body Sensor Location 0x2A38)
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BodySensorLocation {
+    Other,
+    Chest,
+    Wrist,
+    Finger,
+    Hand,
+    EarLobe,
+    Foot,
+}
+
+impl BodySensorLocation {
+    fn from_byte(value: u8) -> Self {
+        match value {
+            1 => Self::Chest,
+            2 => Self::Wrist,
+            3 => Self::Finger,
+            4 => Self::Hand,
+            5 => Self::EarLobe,
+            6 => Self::Foot,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// 心率测量数据 (Heart Rate Measurement 0x2A37)
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartRateMeasurement {
+    /// 心率 (bpm)
+    pub bpm: u16,
+    /// 设备是否支持接触检测
+    pub sensor_contact_supported: bool,
+    /// 是否检测到皮肤接触
+    pub sensor_contact_detected: bool,
+    /// 累计能量消耗 (kJ)
+    pub energy_expended: Option<u16>,
+    /// RR 间期 (毫秒)
+    pub rr_intervals: Vec<f64>,
+    /// 接收时间戳 (Unix 毫秒)
+    pub timestamp: u64,
+}
+
+/// 设备电量
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceBattery {
+    pub device_id: String,
+    /// 电量百分比 (0-100)
+    pub level: u8,
+    /// 是否低于设置中的低电量阈值
+    pub low: bool,
+    pub timestamp: u64,
+}
+
+/// 带设备标识的心率样本
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartRateSample {
+    pub device_id: String,
+    /// 用户为设备设置的标签
+    pub label: Option<String>,
+    /// 心率区间 (0 表示低于区间 1，1-5 为对应区间)，未设置用户资料时为空
+    pub zone: Option<u8>,
+    #[serde(flatten)]
+    pub measurement: HeartRateMeasurement,
+}
+
+/// 心率流错误事件
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamError {
+    pub device_id: String,
+    #[serde(flatten)]
+    pub error: AppError,
+}
+
+/// 心率流停止事件
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStopped {
+    pub device_id: String,
+    /// 本次会话的统计摘要
+    pub summary: Option<SessionSummary>,
+}
+
+/// 单个设备的心率流状态
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStatus {
+    pub device_id: String,
+    pub label: Option<String>,
+    pub is_running: bool,
+    pub battery: Option<DeviceBattery>,
+}
+
+/// 单个设备的心率流会话
+struct StreamSession {
+    task: tokio::task::JoinHandle<()>,
+    battery: Option<DeviceBattery>,
+    /// 数据源是否依赖蓝牙适配器
+    uses_adapter: bool,
 }
 
 /// 全局心率流状态
 struct HeartRateStreamState {
-    task: Option<tokio::task::JoinHandle<()>>,
     selected_device_id: Option<String>,
-    is_running: bool,
+    /// 按设备 ID 索引的会话
+    sessions: BTreeMap<String, StreamSession>,
+    /// 按设备 ID 索引的用户标签
+    labels: BTreeMap<String, String>,
 }
 
 impl HeartRateStreamState {
     const fn new() -> Self {
         Self {
-            task: None,
             selected_device_id: None,
-            is_running: false,
+            sessions: BTreeMap::new(),
+            labels: BTreeMap::new(),
         }
     }
+
+    fn is_running(&self, device_id: &str) -> bool {
+        self.sessions
+            .get(device_id)
+            .is_some_and(|session| !session.task.is_finished())
+    }
+
+    /// 解析目标设备：优先使用传入 ID，否则使用当前选中的设备
+    fn resolve_device_id(&self, device_id: Option<String>) -> AppResult<String> {
+        device_id
+            .or_else(|| self.selected_device_id.clone())
+            .ok_or_else(|| {
+                AppError::new(
+                    ErrorCode::InvalidArgument,
+                    "No device selected. Please select a device first.",
+                )
+            })
+    }
 }
 
 /// 心率流状态管理
@@ -54,7 +221,7 @@ static HEART_RATE_STATE: RwLock<HeartRateStreamState> = RwLock::const_new(HeartR
 
 /// 检查蓝牙适配器是否可用
 #[tauri::command]
-pub async fn bluetooth_available() -> Result<bool, String> {
+pub async fn bluetooth_available() -> AppResult<bool> {
     match Adapter::default().await {
         Some(adapter) => {
             #[cfg(target_os = "linux")]
@@ -83,17 +250,22 @@ pub async fn bluetooth_available() -> Result<bool, String> {
 }
 
 /// 获取适配器并等待可用
-async fn get_adapter() -> Result<Adapter, String> {
+pub(crate) async fn get_adapter() -> AppResult<Adapter> {
     let adapter = Adapter::default()
         .await
-        .ok_or_else(|| "Bluetooth adapter not found".to_string())?;
+        .ok_or_else(|| AppError::new(ErrorCode::AdapterMissing, "Bluetooth adapter not found"))?;
+
+    let adapter_unavailable = |e: bluest::Error| match e.kind() {
+        bluest::error::ErrorKind::NotAuthorized => AppError::from(e),
+        _ => AppError::new(ErrorCode::AdapterOff, format!("Adapter not available: {e}")),
+    };
 
     #[cfg(target_os = "linux")]
     {
         timeout(DBUS_OPERATION_TIMEOUT, adapter.wait_available())
             .await
-            .map_err(|_| "Adapter availability check timeout".to_string())?
-            .map_err(|e| format!("Adapter not available: {e}"))?;
+            .map_err(|_| AppError::new(ErrorCode::AdapterOff, "Adapter availability check timeout"))?
+            .map_err(adapter_unavailable)?;
     }
 
     #[cfg(not(target_os = "linux"))]
@@ -101,39 +273,73 @@ async fn get_adapter() -> Result<Adapter, String> {
         adapter
             .wait_available()
             .await
-            .map_err(|e| format!("Adapter not available: {e}"))?;
+            .map_err(adapter_unavailable)?;
     }
 
     Ok(adapter)
 }
 
 /// 从设备构建设备信息
-async fn device_to_info(device: &Device, connected: bool) -> DeviceInfo {
+pub(crate) async fn device_to_info(device: &Device, connected: bool) -> DeviceInfo {
     let id = device.id().to_string();
     let mac_address = extract_mac_address(&id);
     let name = device.name_async().await.ok();
+    let rssi = device.rssi().await.ok();
 
     DeviceInfo {
         id,
         mac_address,
         name,
         connected,
+        rssi,
+    }
+}
+
+/// 从扫描到的广播构建设备信息
+pub(crate) fn advertising_device_to_info(advertising_device: &AdvertisingDevice) -> DeviceInfo {
+    let device = &advertising_device.device;
+    let id = device.id().to_string();
+    let mac_address = extract_mac_address(&id);
+    let name = advertising_device
+        .adv_data
+        .local_name
+        .clone()
+        .or_else(|| device.name().ok());
+
+    DeviceInfo {
+        id,
+        mac_address,
+        name,
+        connected: false,
+        rssi: advertising_device.rssi,
     }
 }
 
 /// 收集已连接的设备
-async fn collect_connected_devices(
+pub(crate) async fn collect_connected_devices(
     adapter: &Adapter,
     device_ids: &mut std::collections::HashSet<String>,
+    filter: &DeviceFilter,
 ) -> Vec<DeviceInfo> {
     let mut devices = Vec::new();
 
-    match adapter.connected_devices().await {
+    let connected = if filter.services.is_empty() {
+        adapter.connected_devices().await
+    } else {
+        adapter.connected_devices_with_services(&filter.services).await
+    };
+
+    match connected {
         Ok(connected) => {
             for device in connected {
                 let id = device.id().to_string();
-                if device_ids.insert(id.clone()) {
-                    devices.push(device_to_info(&device, true).await);
+                if device_ids.contains(&id) || devices.len() >= filter.max_devices {
+                    continue;
+                }
+                let info = device_to_info(&device, true).await;
+                if filter.matches(&info) {
+                    device_ids.insert(id);
+                    devices.push(info);
                 }
             }
         }
@@ -147,10 +353,12 @@ async fn collect_connected_devices(
 async fn scan_for_devices(
     adapter: &Adapter,
     device_ids: &mut std::collections::HashSet<String>,
+    filter: &DeviceFilter,
+    max_devices: usize,
 ) -> Vec<DeviceInfo> {
     let mut devices = Vec::new();
 
-    let Ok(mut scan) = adapter.discover_devices(&[HRS_UUID]).await else {
+    let Ok(mut scan) = adapter.scan(&filter.services).await else {
         eprintln!("Failed to start device discovery");
         return devices;
     };
@@ -158,20 +366,17 @@ async fn scan_for_devices(
     let start = Instant::now();
     let mut count = 0;
 
-    while start.elapsed() < SCAN_TIMEOUT && count < MAX_DEVICES {
+    while start.elapsed() < SCAN_TIMEOUT && count < max_devices {
         let timeout_result = tokio::time::timeout(SCAN_INTERVAL, scan.next()).await;
 
         match timeout_result {
-            Ok(Some(Ok(device))) => {
-                let id = device.id().to_string();
-                if device_ids.insert(id.clone()) {
-                    devices.push(device_to_info(&device, false).await);
+            Ok(Some(advertising_device)) => {
+                let info = advertising_device_to_info(&advertising_device);
+                if filter.matches(&info) && device_ids.insert(info.id.clone()) {
+                    devices.push(info);
                     count += 1;
                 }
             }
-            Ok(Some(Err(e))) => {
-                eprintln!("Discovery error: {e}");
-            }
             Ok(None) => {
                 eprintln!("Scan completed");
                 break;
@@ -185,56 +390,200 @@ async fn scan_for_devices(
     devices
 }
 
-/// 列出所有可用的蓝牙设备
+/// 列出所有可用的蓝牙设备，未传入过滤条件时使用设置中的过滤条件
 #[tauri::command]
-pub async fn list_devices() -> Result<Vec<DeviceInfo>, String> {
+pub async fn list_devices(filter: Option<ScanFilter>) -> AppResult<Vec<DeviceInfo>> {
+    let filter = match filter {
+        Some(filter) => DeviceFilter::from_settings(&filter)?,
+        None => DeviceFilter::load()?,
+    };
     let adapter = get_adapter().await?;
     let mut device_ids = std::collections::HashSet::new();
     eprintln!("Starting device discovery...");
-    let mut all_devices = collect_connected_devices(&adapter, &mut device_ids).await;
-    let scanned_devices = scan_for_devices(&adapter, &mut device_ids).await;
+    let mut all_devices = collect_connected_devices(&adapter, &mut device_ids, &filter).await;
+    let remaining = filter.max_devices.saturating_sub(all_devices.len());
+    let scanned_devices = scan_for_devices(&adapter, &mut device_ids, &filter, remaining).await;
     all_devices.extend(scanned_devices);
+    // 信号强的设备排在前面
+    all_devices.sort_by_key(|d| std::cmp::Reverse(d.rssi));
+    if settings::load_settings()?.developer_mode {
+        all_devices.extend(simulation::simulated_devices());
+    }
     eprintln!("Discovery completed. Found {} devices", all_devices.len());
     Ok(all_devices)
 }
 
-/// 选择设备
+/// 选择设备（不影响其他设备正在运行的心率流）
 #[tauri::command]
-pub async fn select_device(id: String) -> Result<(), String> {
+pub async fn select_device(id: String) -> AppResult<()> {
     let mut state = HEART_RATE_STATE.write().await;
-    // 如果正在运行，先停止
-    if state.is_running {
-        if let Some(task) = state.task.take() {
-            task.abort();
-            eprintln!("Stopped existing heart rate stream before selecting new device");
-        }
-        state.is_running = false;
-    }
     state.selected_device_id = Some(id.clone());
     eprintln!("Selected device: {id}");
 
     Ok(())
 }
 
+/// 设置设备标签，传入空值则清除
+#[tauri::command]
+pub async fn set_device_label(device_id: String, label: Option<String>) -> AppResult<()> {
+    let mut state = HEART_RATE_STATE.write().await;
+    match label.filter(|l| !l.trim().is_empty()) {
+        Some(label) => {
+            state.labels.insert(device_id, label);
+        }
+        None => {
+            state.labels.remove(&device_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// 获取所有设备的心率流状态
+#[tauri::command]
+pub async fn get_stream_status() -> AppResult<Vec<StreamStatus>> {
+    let state = HEART_RATE_STATE.read().await;
+    Ok(state
+        .sessions
+        .iter()
+        .map(|(device_id, session)| StreamStatus {
+            device_id: device_id.clone(),
+            label: state.labels.get(device_id).cloned(),
+            is_running: !session.task.is_finished(),
+            battery: session.battery.clone(),
+        })
+        .collect())
+}
+
+/// 解析目标设备：优先使用传入 ID，否则使用当前选中的设备
+pub(crate) async fn resolve_device_id(device_id: Option<String>) -> AppResult<String> {
+    HEART_RATE_STATE.read().await.resolve_device_id(device_id)
+}
+
 /// 获取当前选中的设备 ID
 #[tauri::command]
-pub async fn _get_selected_device() -> Result<Option<String>, String> {
+pub async fn _get_selected_device() -> AppResult<Option<String>> {
     let state = HEART_RATE_STATE.read().await;
     Ok(state.selected_device_id.clone())
 }
 
-/// 检查心率流是否正在运行
+/// 读取设备详情（设备信息服务与传感器位置）
 #[tauri::command]
-pub async fn is_heart_rate_streaming() -> Result<bool, String> {
+pub async fn get_device_details(id: Option<String>) -> AppResult<DeviceDetails> {
+    let device_id = HEART_RATE_STATE.read().await.resolve_device_id(id)?;
+
+    let adapter = get_adapter().await?;
+    let device = find_heart_rate_device(&adapter, Some(&device_id)).await?;
+    ensure_connected(&adapter, &device).await?;
+
+    let info = device_to_info(&device, device.is_connected().await).await;
+
+    let mut details = DeviceDetails {
+        info,
+        manufacturer: None,
+        model_number: None,
+        serial_number: None,
+        firmware_revision: None,
+        hardware_revision: None,
+        body_sensor_location: None,
+    };
+
+    match discover_service_characteristics(&device, DEVICE_INFORMATION_UUID, None).await {
+        Ok(characteristics) => {
+            for characteristic in &characteristics {
+                let field = match characteristic.uuid() {
+                    MANUFACTURER_NAME_UUID => &mut details.manufacturer,
+                    MODEL_NUMBER_UUID => &mut details.model_number,
+                    SERIAL_NUMBER_UUID => &mut details.serial_number,
+                    FIRMWARE_REVISION_UUID => &mut details.firmware_revision,
+                    HARDWARE_REVISION_UUID => &mut details.hardware_revision,
+                    _ => continue,
+                };
+                *field = read_string_characteristic(characteristic).await;
+            }
+        }
+        Err(e) => eprintln!("Device information service unavailable: {e}"),
+    }
+
+    match find_characteristic(&device, HRS_UUID, BODY_SENSOR_LOCATION_UUID).await {
+        Ok(characteristic) => match characteristic.read().await {
+            Ok(data) => {
+                details.body_sensor_location = data.first().copied().map(BodySensorLocation::from_byte);
+            }
+            Err(e) => eprintln!("Failed to read body sensor location: {e}"),
+        },
+        Err(e) => eprintln!("Body sensor location unavailable: {e}"),
+    }
+
+    Ok(details)
+}
+
+/// 读取字符串类型的特征值
+async fn read_string_characteristic(characteristic: &bluest::Characteristic) -> Option<String> {
+    match characteristic.read().await {
+        Ok(data) => {
+            let value = String::from_utf8_lossy(&data)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string();
+            (!value.is_empty()).then_some(value)
+        }
+        Err(e) => {
+            eprintln!("Failed to read characteristic {}: {e}", characteristic.uuid());
+            None
+        }
+    }
+}
+
+/// 重置累计能量消耗（写入心率控制点）
+#[tauri::command]
+pub async fn reset_energy_expended(device_id: Option<String>) -> AppResult<()> {
+    let device_id = HEART_RATE_STATE.read().await.resolve_device_id(device_id)?;
+
+    let adapter = get_adapter().await?;
+    let device = find_heart_rate_device(&adapter, Some(&device_id)).await?;
+    ensure_connected(&adapter, &device).await?;
+
+    write_reset_energy(&device).await
+}
+
+/// 向心率控制点写入重置能量消耗命令，设备需已连接
+async fn write_reset_energy(device: &Device) -> AppResult<()> {
+    let control_point = find_characteristic(device, HRS_UUID, HR_CONTROL_POINT_UUID).await?;
+
+    control_point.write(&[HR_CONTROL_POINT_RESET_ENERGY]).await?;
+
+    eprintln!("Energy expended reset for device: {}", device.id());
+
+    Ok(())
+}
+
+/// 获取设备的最新电量
+#[tauri::command]
+pub async fn get_device_battery(device_id: Option<String>) -> AppResult<Option<DeviceBattery>> {
     let state = HEART_RATE_STATE.read().await;
-    Ok(state.is_running)
+    let device_id = state.resolve_device_id(device_id)?;
+    Ok(state
+        .sessions
+        .get(&device_id)
+        .and_then(|session| session.battery.clone()))
+}
+
+/// 检查心率流是否正在运行，未指定设备时检查是否有任意设备在运行
+#[tauri::command]
+pub async fn is_heart_rate_streaming(device_id: Option<String>) -> AppResult<bool> {
+    let state = HEART_RATE_STATE.read().await;
+    Ok(match device_id {
+        Some(id) => state.is_running(&id),
+        None => state.sessions.keys().any(|id| state.is_running(id)),
+    })
 }
 
 #[cfg(target_os = "linux")]
 async fn connect_device_linux(
     adapter: &Adapter,
     device: &Device,
-) -> Result<(), Box<dyn Error>> {
+) -> AppResult<()> {
     eprintln!("Linux: Starting device connection with D-Bus optimizations...");
 
     // 配对（失败不致命）
@@ -279,9 +628,32 @@ async fn connect_device_linux(
 
 }
 
+/// 确保设备已连接（按平台选择连接方式）
+pub(crate) async fn ensure_connected(adapter: &Adapter, device: &Device) -> AppResult<()> {
+    // 已连接（如心率流正在运行）时不再重复配对、连接与服务发现
+    if device.is_connected().await {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    let result = connect_device_linux(adapter, device).await;
+
+    #[cfg(not(target_os = "linux"))]
+    let result = connect_device_standard(adapter, device).await;
+
+    // 未分类的蓝牙错误统一视为连接失败
+    result.map_err(|e| match e.code {
+        ErrorCode::Bluetooth => AppError::new(
+            ErrorCode::ConnectionFailed,
+            format!("Failed to connect device: {}", e.message),
+        ),
+        _ => e,
+    })
+}
+
 /// 非 Linux 平台的标准连接方法
 #[cfg(not(target_os = "linux"))]
-async fn connect_device_standard(adapter: &Adapter, device: &Device) -> Result<(), Box<dyn Error>> {
+async fn connect_device_standard(adapter: &Adapter, device: &Device) -> AppResult<()> {
     if !device.is_connected().await {
         adapter.connect_device(device).await?;
         eprintln!("Device connected");
@@ -290,10 +662,10 @@ async fn connect_device_standard(adapter: &Adapter, device: &Device) -> Result<(
 }
 
 /// 查找心率设备
-async fn find_heart_rate_device(
+pub(crate) async fn find_heart_rate_device(
     adapter: &Adapter,
     device_id: Option<&str>,
-) -> Result<Device, String> {
+) -> AppResult<Device> {
     // 优先检查已连接的设备
     if let Ok(connected) = adapter.connected_devices_with_services(&[HRS_UUID]).await {
         if let Some(device) = find_device_in_list(&connected, device_id) {
@@ -316,11 +688,10 @@ fn find_device_in_list(devices: &[Device], target_id: Option<&str>) -> Option<De
 }
 
 /// 扫描并查找设备
-async fn scan_for_device(adapter: &Adapter, device_id: Option<&str>) -> Result<Device, String> {
-    let mut scan = adapter
-        .discover_devices(&[HRS_UUID])
-        .await
-        .map_err(|e| format!("Failed to start scan: {e}"))?;
+async fn scan_for_device(adapter: &Adapter, device_id: Option<&str>) -> AppResult<Device> {
+    // 指定设备时按 ID 匹配，不要求设备广播心率服务
+    let services: &[Uuid] = if device_id.is_some() { &[] } else { &[HRS_UUID] };
+    let mut scan = adapter.discover_devices(services).await?;
 
     let start = Instant::now();
 
@@ -346,64 +717,320 @@ async fn scan_for_device(adapter: &Adapter, device_id: Option<&str>) -> Result<D
         }
     }
 
-    Err("Device not found during scan".to_string())
+    Err(AppError::new(ErrorCode::DeviceNotFound, "Device not found during scan"))
 }
 
-/// 开始心率数据流
+/// 开始心率数据流。
+/// 立即返回，设备查找与连接在后台任务中进行，进度通过 "connection-state" 事件上报，
+/// 失败时发送 "heart-rate-error" 事件；连接过程中可随时调用 `stop_heart_rate_stream` 取消
 #[tauri::command]
-pub async fn start_heart_rate_stream(app: AppHandle) -> Result<(), String> {
+pub async fn start_heart_rate_stream(
+    app: AppHandle,
+    device_id: Option<String>,
+    label: Option<String>,
+) -> AppResult<()> {
+    start_stream(app, device_id, label, false).await
+}
+
+/// 开始心率流，设备不在范围内时持续扫描直到设备出现（用于自动连接），
+/// 期间保持 Scanning 状态，不发送错误事件
+pub(crate) async fn start_heart_rate_stream_when_found(
+    app: AppHandle,
+    device_id: String,
+    label: Option<String>,
+) -> AppResult<()> {
+    start_stream(app, Some(device_id), label, true).await
+}
+
+async fn start_stream(
+    app: AppHandle,
+    device_id: Option<String>,
+    label: Option<String>,
+    wait_for_device: bool,
+) -> AppResult<()> {
     let mut state = HEART_RATE_STATE.write().await;
 
-    // 检查是否已经在运行
-    if state.is_running {
-        return Err("Heart rate stream is already running".to_string());
+    // 未指定设备时使用选中的设备
+    let device_id = state.resolve_device_id(device_id)?;
+
+    // 检查该设备是否已经在运行
+    if state.is_running(&device_id) {
+        return Err(AppError::new(
+            ErrorCode::InvalidState,
+            format!("Heart rate stream is already running for device: {device_id}"),
+        ));
     }
 
-    // 检查是否有选中的设备
-    let device_id = state
-        .selected_device_id
-        .clone()
-        .ok_or("No device selected. Please select a device first.")?;
+    if let Some(label) = label.filter(|l| !l.trim().is_empty()) {
+        state.labels.insert(device_id.clone(), label);
+    }
 
     eprintln!("Starting heart rate stream for device: {device_id}");
 
-    let adapter = get_adapter().await?;
-    let device = find_heart_rate_device(&adapter, Some(&device_id)).await?;
+    connection::set_connection_state(&app, &device_id, ConnectionState::Scanning, 0, None).await;
 
-    // 启动新任务
-    let task = tokio::task::spawn(async move {
-        if let Err(e) = handle_heart_rate_stream(&adapter, &device, &app).await {
-            eprintln!("Heart rate stream error: {e}");
-            let _ = app.emit("heart-rate-error", format!("{e}"));
-            let _ = app.emit("heart-rate-stopped", ());
+    let source = match create_source(&device_id, wait_for_device) {
+        Ok(source) => source,
+        Err(e) => {
+            connection::set_connection_state(&app, &device_id, ConnectionState::Failed, 0, Some(e.message.clone())).await;
+            return Err(e);
         }
-    });
+    };
 
-    state.task = Some(task);
-    state.is_running = true;
+    spawn_session(&mut state, app, device_id, source);
 
     eprintln!("✓ Heart rate stream started");
 
     Ok(())
 }
 
-/// 停止心率数据流
-#[tauri::command]
-pub async fn stop_heart_rate_stream(app: AppHandle) -> Result<(), String> {
+/// 使用指定数据源启动心率流（如回放文件）
+pub(crate) async fn start_stream_with_source(
+    app: AppHandle,
+    device_id: String,
+    label: Option<String>,
+    source: Box<dyn HeartRateSource>,
+) -> AppResult<()> {
     let mut state = HEART_RATE_STATE.write().await;
 
-    if let Some(task) = state.task.take() {
-        task.abort();
-        state.is_running = false;
-        eprintln!("Heart rate stream stopped");
+    if state.is_running(&device_id) {
+        return Err(AppError::new(
+            ErrorCode::InvalidState,
+            format!("Heart rate stream is already running for device: {device_id}"),
+        ));
+    }
+
+    if let Some(label) = label.filter(|l| !l.trim().is_empty()) {
+        state.labels.insert(device_id.clone(), label);
+    }
+
+    eprintln!("Starting heart rate stream for device: {device_id}");
+    spawn_session(&mut state, app, device_id, source);
+
+    Ok(())
+}
+
+/// 在独立任务中运行数据源并登记会话
+fn spawn_session(
+    state: &mut HeartRateStreamState,
+    app: AppHandle,
+    device_id: String,
+    source: Box<dyn HeartRateSource>,
+) {
+    let task_device_id = device_id.clone();
+    let label = state.labels.get(&device_id).cloned();
+    let uses_adapter = source.uses_adapter();
+    let task = tokio::task::spawn(async move {
+        let device_id = task_device_id;
+        begin_session(&device_id, label.as_deref()).await;
+        let tracker = ConnectionTracker::new(app.clone(), device_id.clone());
+        match source.run(app.clone(), tracker).await {
+            Ok(_) => connection::mark_stopped(&app, &device_id).await,
+            Err(e) => {
+                let error = AppError::from_boxed(e, ErrorCode::ConnectionLost);
+                eprintln!("Heart rate stream error ({device_id}): {error}");
+                connection::mark_failed(&app, &device_id, error.message.clone()).await;
+                let _ = app.emit(
+                    "heart-rate-error",
+                    StreamError {
+                        device_id: device_id.clone(),
+                        error,
+                    },
+                );
+            }
+        }
+
+        let summary = finish_session(&device_id).await;
+
+        // 取消不会立即生效，只移除本任务自己的会话，避免删掉之后为同一设备启动的新会话
+        let mut state = HEART_RATE_STATE.write().await;
+        if state
+            .sessions
+            .get(&device_id)
+            .is_some_and(|session| session.task.id() == tokio::task::id())
+        {
+            state.sessions.remove(&device_id);
+        }
+        drop(state);
+
+        let _ = app.emit("heart-rate-stopped", StreamStopped { device_id, summary });
+    });
+
+    state.sessions.insert(
+        device_id,
+        StreamSession {
+            task,
+            battery: None,
+            uses_adapter,
+        },
+    );
+}
+
+/// 开始记录会话：历史、统计、心率区间与 HRV
+async fn begin_session(device_id: &str, label: Option<&str>) {
+    history::open_session(device_id, label);
+    summary::begin(device_id).await;
+    zone::begin(device_id).await;
+    hrv::begin(device_id).await;
+}
+
+/// 结束会话记录，保存并返回会话统计摘要
+async fn finish_session(device_id: &str) -> Option<SessionSummary> {
+    let time_in_zones = zone::end(device_id).await;
+    let hrv = hrv::finish(device_id).await;
+    let summary = summary::finish(device_id, time_in_zones, hrv).await;
+    history::close_session(device_id, summary.as_ref());
+    summary
+}
+
+/// 根据设备 ID 创建数据源
+fn create_source(device_id: &str, wait_for_device: bool) -> AppResult<Box<dyn HeartRateSource>> {
+    if let Some(profile) = SimulationProfile::from_device_id(device_id) {
+        let settings = settings::load_settings()?.simulation;
+        return Ok(Box::new(SimulatedSource::new(profile, settings)));
+    }
+
+    Ok(Box::new(BleSource {
+        device_id: device_id.to_string(),
+        wait_for_device,
+    }))
+}
+
+/// 蓝牙 GATT 心率数据源
+struct BleSource {
+    device_id: String,
+    /// 未找到设备时继续扫描而不是失败
+    wait_for_device: bool,
+}
+
+impl BleSource {
+    /// 查找设备并处理心率流，查找与连接都在会话任务中进行，可随任务取消
+    async fn stream(
+        self,
+        app: AppHandle,
+        mut tracker: ConnectionTracker,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let adapter = get_adapter().await?;
+        let device = loop {
+            match find_heart_rate_device(&adapter, Some(&self.device_id)).await {
+                Ok(device) => break device,
+                Err(e) if self.wait_for_device && e.code == ErrorCode::DeviceNotFound => {
+                    eprintln!("Device {} not in range, scanning again in {DEVICE_WAIT_INTERVAL:?}", self.device_id);
+                    tokio::time::sleep(DEVICE_WAIT_INTERVAL).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        handle_heart_rate_stream(&adapter, &device, &app, &mut tracker).await
+    }
+}
+
+impl HeartRateSource for BleSource {
+    fn run(self: Box<Self>, app: AppHandle, tracker: ConnectionTracker) -> SourceFuture {
+        let source = *self;
+        Box::pin(source.stream(app, tracker))
+    }
+
+    fn uses_adapter(&self) -> bool {
+        true
+    }
+}
+
+/// 停止心率数据流，未指定设备时停止全部（包括因适配器不可用而暂停的心率流）
+#[tauri::command]
+pub async fn stop_heart_rate_stream(app: AppHandle, device_id: Option<String>) -> AppResult<()> {
+    let sessions: Vec<(String, StreamSession)> = {
+        let mut state = HEART_RATE_STATE.write().await;
+        match &device_id {
+            Some(id) => state.sessions.remove_entry(id).into_iter().collect(),
+            None => std::mem::take(&mut state.sessions).into_iter().collect(),
+        }
+    };
+
+    // 不在持有心率流状态锁时等待暂停列表，避免与 pause_streams 的加锁顺序相反
+    let paused = adapter::take_paused_streams(device_id.as_deref()).await;
+
+    if sessions.is_empty() && paused.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidState, "No heart rate stream is running"));
+    }
+
+    // 暂停时会话已经结束，只需切换状态并广播停止事件
+    for device_id in paused {
+        eprintln!("Paused heart rate stream stopped: {device_id}");
+        connection::mark_stopped(&app, &device_id).await;
+        let _ = app.emit("heart-rate-stopped", StreamStopped { device_id, summary: None });
+    }
+
+    for (device_id, session) in sessions {
+        session.task.abort();
+        eprintln!("Heart rate stream stopped: {device_id}");
+        let summary = finish_session(&device_id).await;
+        connection::mark_stopped(&app, &device_id).await;
 
         // 全局广播停止事件
-        let _ = app.emit("heart-rate-stopped", ());
+        let _ = app.emit("heart-rate-stopped", StreamStopped { device_id, summary });
+    }
 
-        Ok(())
-    } else {
-        Err("No heart rate stream is running".to_string())
+    Ok(())
+}
+
+/// 暂停依赖蓝牙适配器的心率流（适配器不可用时），返回被暂停的设备 ID。
+/// 回放与模拟心率流不受影响
+pub(crate) async fn pause_streams(app: &AppHandle) -> Vec<String> {
+    let sessions: Vec<(String, StreamSession)> = {
+        let mut state = HEART_RATE_STATE.write().await;
+        let ids: Vec<String> = state
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.uses_adapter)
+            .map(|(device_id, _)| device_id.clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|device_id| state.sessions.remove_entry(&device_id))
+            .collect()
+    };
+
+    let mut paused = Vec::new();
+    for (device_id, session) in sessions {
+        if session.task.is_finished() {
+            continue;
+        }
+        session.task.abort();
+        eprintln!("Heart rate stream paused: {device_id}");
+        finish_session(&device_id).await;
+        connection::set_connection_state(
+            app,
+            &device_id,
+            ConnectionState::Reconnecting,
+            0,
+            Some("Bluetooth adapter unavailable".to_string()),
+        )
+        .await;
+        paused.push(device_id);
     }
+
+    paused
+}
+
+/// 广播带设备标识的心率样本
+pub(crate) async fn emit_heart_rate_sample(
+    app: &AppHandle,
+    device_id: &str,
+    measurement: HeartRateMeasurement,
+) {
+    let label = HEART_RATE_STATE.read().await.labels.get(device_id).cloned();
+    let zone = zone::classify(app, device_id, measurement.bpm, measurement.timestamp).await;
+    let sample = HeartRateSample {
+        device_id: device_id.to_string(),
+        label,
+        zone,
+        measurement,
+    };
+    summary::record_sample(&sample).await;
+    hrv::record_sample(app, &sample).await;
+    history::record_sample(&sample);
+    let _ = app.emit("heart-rate-update", sample);
 }
 
 /// 处理心率数据流
@@ -411,28 +1038,43 @@ async fn handle_heart_rate_stream(
     adapter: &Adapter,
     device: &Device,
     app: &AppHandle,
+    tracker: &mut ConnectionTracker,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let mut consecutive_errors = 0;
-    const MAX_CONSECUTIVE_ERRORS: u32 = 3;
+    let current_settings = settings::load_settings().unwrap_or_default();
+    let policy = current_settings.reconnect;
+    // 会话首次订阅成功后重置能量消耗，重连时不再重置
+    let mut reset_energy = current_settings.reset_energy_on_start;
 
     loop {
-        match process_heart_rate_notifications(adapter, device, app).await {
+        match process_heart_rate_notifications(adapter, device, app, tracker, &mut reset_energy).await {
             Ok(_) => {
                 eprintln!("Heart rate notifications ended normally");
                 break;
             }
             Err(e) => {
-                consecutive_errors += 1;
-                eprintln!("Error in heart rate stream (attempt {}/{}): {e}",
-                          consecutive_errors, MAX_CONSECUTIVE_ERRORS);
-
-                if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                    return Err(format!("Failed after {} consecutive errors", MAX_CONSECUTIVE_ERRORS).into());
+                let error = AppError::from_boxed(e, ErrorCode::ConnectionLost);
+                tracker.record_error(error.message.clone());
+                let attempt = tracker.attempt();
+                eprintln!("Error in heart rate stream (attempt {}/{}): {error}",
+                          attempt,
+                          policy.max_attempts.map_or("∞".to_string(), |max| max.to_string()));
+
+                if !policy.allows_attempt(attempt) {
+                    // 保留最后一次错误的错误码，便于前端给出对应提示
+                    return Err(AppError::new(
+                        error.code,
+                        format!("Failed after {attempt} consecutive errors: {}", error.message),
+                    )
+                    .into());
                 }
 
-                // 等待后重试
-                eprintln!("Waiting before retry...");
-                tokio::time::sleep(RECONNECT_DELAY).await;
+                tracker.set(ConnectionState::Reconnecting).await;
+                summary::record_reconnect(&device.id().to_string()).await;
+
+                // 按退避策略等待后重试
+                let delay = policy.delay_for_attempt(attempt);
+                eprintln!("Waiting {delay:?} before retry...");
+                tokio::time::sleep(delay).await;
 
                 // 尝试重新连接
                 eprintln!("Attempting to reconnect...");
@@ -464,15 +1106,20 @@ async fn process_heart_rate_notifications(
     adapter: &Adapter,
     device: &Device,
     app: &AppHandle,
+    tracker: &mut ConnectionTracker,
+    reset_energy: &mut bool,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     // 确保设备已连接
+    tracker.set(ConnectionState::Connecting).await;
+
     #[cfg(target_os = "linux")]
-    connect_device_linux(adapter, device).await.expect("linux connect");
+    connect_device_linux(adapter, device).await?;
 
     #[cfg(not(target_os = "linux"))]
     let _=connect_device_standard(adapter, device).await;
 
     // 查找心率服务和特征
+    tracker.set(ConnectionState::DiscoveringServices).await;
     let heart_rate_measurement = find_heart_rate_characteristic_with_retry(device).await?;
 
     eprintln!("Starting heart rate notifications...");
@@ -482,7 +1129,8 @@ async fn process_heart_rate_notifications(
     let mut updates = {
         timeout(DBUS_OPERATION_TIMEOUT, heart_rate_measurement.notify())
             .await
-            .map_err(|_| "Notification subscription timeout")??
+            .map_err(|_| AppError::new(ErrorCode::SubscribeTimeout, "Notification subscription timeout"))?
+            .map_err(AppError::from)?
     };
 
     #[cfg(not(target_os = "linux"))]
@@ -490,33 +1138,176 @@ async fn process_heart_rate_notifications(
 
     eprintln!("Successfully subscribed to heart rate notifications");
 
+    // 设备不支持控制点时仅记录，不影响心率流
+    if std::mem::take(reset_energy) {
+        if let Err(e) = write_reset_energy(device).await {
+            eprintln!("Failed to reset energy expended: {e}");
+        }
+    }
+    tracker.reset_attempts();
+    tracker.set(ConnectionState::Subscribed).await;
+    known_devices::remember_device(device_to_info(device, true).await).await;
+
+    let device_id = device.id().to_string();
+
+    let current_settings = settings::load_settings().unwrap_or_default();
+    let stale_timeout = current_settings.stale_timeout();
+
+    // 电量服务（可选，失败不影响心率流）
+    let low_battery_threshold = current_settings.low_battery_threshold;
+    let mut low_battery_warned = false;
+
+    let battery_level = match find_characteristic(device, BATTERY_SERVICE_UUID, BATTERY_LEVEL_UUID).await {
+        Ok(characteristic) => Some(characteristic),
+        Err(e) => {
+            eprintln!("Battery service unavailable: {e}");
+            None
+        }
+    };
+
+    let mut battery_updates = None;
+    if let Some(characteristic) = &battery_level {
+        match characteristic.read().await {
+            Ok(data) => {
+                handle_battery_level(app, &device_id, &data, low_battery_threshold, &mut low_battery_warned).await;
+            }
+            Err(e) => eprintln!("Failed to read battery level: {e}"),
+        }
+
+        let supports_notify = characteristic
+            .properties()
+            .await
+            .map(|p| p.notify)
+            .unwrap_or(false);
+        if supports_notify {
+            match characteristic.notify().await {
+                Ok(stream) => battery_updates = Some(stream),
+                Err(e) => eprintln!("Failed to subscribe to battery level: {e}"),
+            }
+        }
+    }
+
+    // 信号质量统计
+    let mut notification_stats = NotificationStats::new();
+    let mut signal_interval = tokio::time::interval(SIGNAL_SAMPLE_INTERVAL);
+    signal_interval.tick().await;
+
+    // 信号中断检测：从订阅成功或上一次收到心率开始计时
+    let mut last_update_at = Instant::now();
+    let mut last_update = None;
+
     // 处理通知流
-    while let Some(update_result) = updates.next().await {
-        match update_result {
-            Ok(heart_rate_data) => {
-                match parse_heart_rate(&heart_rate_data) {
-                    Ok(heart_rate_value) => {
-                        // 全局广播心率更新
-                        let _ = app.emit("heart-rate-update", heart_rate_value);
+    loop {
+        tokio::select! {
+            update_result = updates.next() => {
+                match update_result {
+                    Some(Ok(heart_rate_data)) => {
+                        last_update_at = Instant::now();
+                        last_update = Some(current_timestamp_millis());
+                        notification_stats.record();
+                        capture::record(&device_id, &heart_rate_data).await;
+                        match parse_heart_rate(&heart_rate_data) {
+                            Ok(measurement) => {
+                                // 全局广播心率更新
+                                emit_heart_rate_sample(app, &device_id, measurement).await;
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to parse heart rate data: {e}");
+                            }
+                        }
+                    }
+                    Some(Err(e)) => {
+                        return Err(AppError::from(e).into());
+                    }
+                    None => break,
+                }
+            }
+            Some(battery_result) = async {
+                match battery_updates.as_mut() {
+                    Some(stream) => stream.next().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match battery_result {
+                    Ok(data) => {
+                        handle_battery_level(app, &device_id, &data, low_battery_threshold, &mut low_battery_warned).await;
                     }
                     Err(e) => {
-                        eprintln!("Failed to parse heart rate data: {e}");
+                        eprintln!("Battery level update error: {e}");
+                        battery_updates = None;
                     }
                 }
             }
-            Err(e) => {
-                return Err(format!("Heart rate update error: {e}").into());
+            _ = signal_interval.tick() => {
+                let rssi = device.rssi().await.ok();
+                let quality = notification_stats.report(&device_id, rssi);
+                let _ = app.emit("signal-quality", quality);
+            }
+            _ = async {
+                match stale_timeout {
+                    Some(stale_timeout) => tokio::time::sleep_until((last_update_at + stale_timeout).into()).await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                // 连接仍在说明手环主动停止了心率推送，否则为链路中断
+                let reason = if device.is_connected().await {
+                    StaleReason::BroadcastEnded
+                } else {
+                    StaleReason::LinkLost
+                };
+                signal::emit_stale(app, &device_id, reason, last_update, last_update_at.elapsed());
+                return Err(AppError::new(
+                    ErrorCode::ConnectionLost,
+                    format!("No heart rate data received ({reason:?})"),
+                )
+                .into());
             }
         }
     }
 
-    Err("Notification stream ended".into())
+    Err(AppError::new(ErrorCode::ConnectionLost, "Notification stream ended").into())
+}
+
+/// 处理电量数据并广播
+async fn handle_battery_level(
+    app: &AppHandle,
+    device_id: &str,
+    data: &[u8],
+    low_threshold: u8,
+    low_warned: &mut bool,
+) {
+    let Some(&level) = data.first() else {
+        eprintln!("Empty battery level data");
+        return;
+    };
+
+    let low = level <= low_threshold;
+    let battery = DeviceBattery {
+        device_id: device_id.to_string(),
+        level,
+        low,
+        timestamp: current_timestamp_millis(),
+    };
+
+    if let Some(session) = HEART_RATE_STATE.write().await.sessions.get_mut(device_id) {
+        session.battery = Some(battery.clone());
+    }
+    let _ = app.emit("device-battery", &battery);
+
+    // 低电量只提醒一次，电量回升后重置
+    if low && !*low_warned {
+        eprintln!("Warning: Device battery low ({level}%)");
+        let _ = app.emit("device-battery-low", &battery);
+        *low_warned = true;
+    } else if !low {
+        *low_warned = false;
+    }
 }
 
 /// 查找心率特征
 async fn find_heart_rate_characteristic_with_retry(
     device: &Device,
-) -> Result<bluest::Characteristic, Box<dyn Error + Send + Sync>> {
+) -> AppResult<bluest::Characteristic> {
     for attempt in 1..=MAX_RETRIES {
         eprintln!("Finding heart rate characteristic (attempt {}/{})", attempt, MAX_RETRIES);
 
@@ -549,85 +1340,163 @@ async fn find_heart_rate_characteristic_with_retry(
         }
     }
 
-    Err("Failed to find heart rate characteristic after all retries".into())
+    Err(AppError::new(
+        ErrorCode::ServiceMissing,
+        "Failed to find heart rate characteristic after all retries",
+    ))
 }
 
 /// 查找心率特征
 async fn find_heart_rate_characteristic(
     device: &Device,
-) -> Result<bluest::Characteristic, Box<dyn Error + Send + Sync>> {
+) -> AppResult<bluest::Characteristic> {
+    find_characteristic(device, HRS_UUID, HRM_UUID).await
+}
+
+/// 发现指定服务下的特征，指定特征 UUID 时只发现该特征
+async fn discover_service_characteristics(
+    device: &Device,
+    service_uuid: Uuid,
+    characteristic_uuid: Option<Uuid>,
+) -> AppResult<Vec<bluest::Characteristic>> {
     #[cfg(target_os = "linux")]
-    let heart_rate_services = {
-        timeout(DBUS_OPERATION_TIMEOUT, device.discover_services_with_uuid(HRS_UUID))
+    let services = {
+        timeout(DBUS_OPERATION_TIMEOUT, device.discover_services_with_uuid(service_uuid))
             .await
-            .map_err(|_| "Service discovery timeout")??
+            .map_err(|_| AppError::new(ErrorCode::ConnectionFailed, "Service discovery timeout"))??
     };
 
     #[cfg(not(target_os = "linux"))]
-    let heart_rate_services = device.discover_services_with_uuid(HRS_UUID).await?;
+    let services = device.discover_services_with_uuid(service_uuid).await?;
 
-    let heart_rate_service = heart_rate_services
+    let service = services
         .first()
-        .ok_or("Device does not have heart rate service")?;
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::ServiceMissing, format!("Device does not have service {service_uuid}"))
+        })?;
+
+    let discover = async {
+        match characteristic_uuid {
+            Some(uuid) => service.discover_characteristics_with_uuid(uuid).await,
+            None => service.discover_characteristics().await,
+        }
+    };
 
     #[cfg(target_os = "linux")]
     tokio::time::sleep(OPERATION_DELAY).await;
 
     #[cfg(target_os = "linux")]
-    let heart_rate_measurements = {
-        timeout(
-            DBUS_OPERATION_TIMEOUT,
-            heart_rate_service.discover_characteristics_with_uuid(HRM_UUID)
-        )
+    let characteristics = {
+        timeout(DBUS_OPERATION_TIMEOUT, discover)
             .await
-            .map_err(|_| "Characteristic discovery timeout")??
+            .map_err(|_| AppError::new(ErrorCode::ConnectionFailed, "Characteristic discovery timeout"))??
     };
 
     #[cfg(not(target_os = "linux"))]
-    let heart_rate_measurements = heart_rate_service
-        .discover_characteristics_with_uuid(HRM_UUID)
-        .await?;
+    let characteristics = discover.await?;
 
-    let heart_rate_measurement = heart_rate_measurements
-        .first()
-        .ok_or("No heart rate measurement characteristic found")?;
+    Ok(characteristics)
+}
 
-    Ok(heart_rate_measurement.clone())
+/// 在指定服务下查找特征
+async fn find_characteristic(
+    device: &Device,
+    service_uuid: Uuid,
+    characteristic_uuid: Uuid,
+) -> AppResult<bluest::Characteristic> {
+    discover_service_characteristics(device, service_uuid, Some(characteristic_uuid))
+        .await?
+        .into_iter()
+        .find(|c| c.uuid() == characteristic_uuid)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::ServiceMissing, format!("No characteristic {characteristic_uuid} found"))
+        })
 }
 
 /// 解析心率数据
-fn parse_heart_rate(data: &[u8]) -> Result<u16, Box<dyn Error>> {
+pub(crate) fn parse_heart_rate(data: &[u8]) -> Result<HeartRateMeasurement, Box<dyn Error + Send + Sync>> {
     if data.is_empty() {
         return Err("Empty heart rate data".into());
     }
 
     let flags = data[0];
+    let mut offset = 1;
 
-    let heart_rate_value = if flags & 0b0000_0001 != 0 {
-        if data.len() < 3 {
+    let bpm = if flags & 0b0000_0001 != 0 {
+        if data.len() < offset + 2 {
             return Err("Insufficient data for 16-bit heart rate".into());
         }
-        u16::from_le_bytes([data[1], data[2]])
+        let value = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        offset += 2;
+        value
     } else {
-        if data.len() < 2 {
+        if data.len() < offset + 1 {
             return Err("Insufficient data for 8-bit heart rate".into());
         }
-        data[1] as u16
+        let value = data[offset] as u16;
+        offset += 1;
+        value
     };
 
-    if flags & 0b0000_0100 != 0 {
-        let sensor_contact = flags & 0b0000_0010 != 0;
-        if !sensor_contact {
-            eprintln!("Warning: Sensor contact lost");
-        }
+    let sensor_contact_supported = flags & 0b0000_0100 != 0;
+    let sensor_contact_detected = sensor_contact_supported && flags & 0b0000_0010 != 0;
+    if sensor_contact_supported && !sensor_contact_detected {
+        eprintln!("Warning: Sensor contact lost");
     }
 
-    Ok(heart_rate_value)
+    // 能量消耗 (kJ)
+    let energy_expended = if flags & 0b0000_1000 != 0 {
+        if data.len() < offset + 2 {
+            return Err("Insufficient data for energy expended".into());
+        }
+        let value = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        offset += 2;
+        Some(value)
+    } else {
+        None
+    };
+
+    // RR 间期，单位 1/1024 秒，转换为毫秒
+    let rr_intervals = if flags & 0b0001_0000 != 0 {
+        data[offset..]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]) as f64 * 1000.0 / 1024.0)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(HeartRateMeasurement {
+        bpm,
+        sensor_contact_supported,
+        sensor_contact_detected,
+        energy_expended,
+        rr_intervals,
+        timestamp: current_timestamp_millis(),
+    })
+}
+
+/// 当前时间戳（毫秒）
+pub(crate) fn current_timestamp_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
 }
 
+/// 随机数（取自标准库哈希的随机密钥），用于重连抖动与模拟数据，不可用于加密
+pub(crate) fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// 样本从 `from` 持续到 `until` 的时长 (毫秒)，数据中断时最多计入 `MAX_SAMPLE_GAP_MS`，
+/// 避免断线期间被计入阈值或区间时间
+pub(crate) fn sample_duration(from: u64, until: u64) -> u64 {
+    until.saturating_sub(from).min(MAX_SAMPLE_GAP_MS)
+}
 
 /// 从设备 ID 中提取 MAC 地址
-fn extract_mac_address(device_id: &str) -> Option<String> {
+pub(crate) fn extract_mac_address(device_id: &str) -> Option<String> {
     let target = device_id.split('#').last().unwrap_or(device_id);
 
     let hex_only: String = target
@@ -647,4 +1516,135 @@ fn extract_mac_address(device_id: &str) -> Option<String> {
             .collect::<Vec<_>>()
             .join(":")
     )
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RR 间期原始值 (1/1024 秒) 转换为毫秒
+    fn rr_ms(raw: u16) -> f64 {
+        raw as f64 * 1000.0 / 1024.0
+    }
+
+    #[test]
+    fn parses_heart_rate_measurements() {
+        struct Case {
+            name: &'static str,
+            data: &'static [u8],
+            bpm: u16,
+            contact: (bool, bool),
+            energy: Option<u16>,
+            rr: &'static [u16],
+        }
+
+        let cases = [
+            Case {
+                name: "8-bit bpm",
+                data: &[0x00, 0x48],
+                bpm: 72,
+                contact: (false, false),
+                energy: None,
+                rr: &[],
+            },
+            Case {
+                name: "16-bit bpm",
+                data: &[0x01, 0x2C, 0x01],
+                bpm: 300,
+                contact: (false, false),
+                energy: None,
+                rr: &[],
+            },
+            Case {
+                name: "contact detected without support bit is ignored",
+                data: &[0x02, 0x48],
+                bpm: 72,
+                contact: (false, false),
+                energy: None,
+                rr: &[],
+            },
+            Case {
+                name: "contact supported, not detected",
+                data: &[0x04, 0x48],
+                bpm: 72,
+                contact: (true, false),
+                energy: None,
+                rr: &[],
+            },
+            Case {
+                name: "contact supported and detected",
+                data: &[0x06, 0x48],
+                bpm: 72,
+                contact: (true, true),
+                energy: None,
+                rr: &[],
+            },
+            Case {
+                name: "energy expended",
+                data: &[0x08, 0x48, 0x34, 0x12],
+                bpm: 72,
+                contact: (false, false),
+                energy: Some(0x1234),
+                rr: &[],
+            },
+            Case {
+                name: "multiple rr intervals",
+                data: &[0x10, 0x48, 0x55, 0x03, 0x40, 0x03, 0x00, 0x04],
+                bpm: 72,
+                contact: (false, false),
+                energy: None,
+                rr: &[0x0355, 0x0340, 0x0400],
+            },
+            Case {
+                name: "16-bit bpm, contact, energy and rr",
+                data: &[0x1F, 0x5A, 0x00, 0x10, 0x00, 0x00, 0x04],
+                bpm: 90,
+                contact: (true, true),
+                energy: Some(16),
+                rr: &[0x0400],
+            },
+            Case {
+                name: "odd trailing rr byte is dropped",
+                data: &[0x10, 0x48, 0x55, 0x03, 0x40],
+                bpm: 72,
+                contact: (false, false),
+                energy: None,
+                rr: &[0x0355],
+            },
+        ];
+
+        for case in cases {
+            let measurement = parse_heart_rate(case.data).unwrap_or_else(|e| panic!("{}: {e}", case.name));
+            assert_eq!(measurement.bpm, case.bpm, "{}", case.name);
+            assert_eq!(
+                (measurement.sensor_contact_supported, measurement.sensor_contact_detected),
+                case.contact,
+                "{}",
+                case.name
+            );
+            assert_eq!(measurement.energy_expended, case.energy, "{}", case.name);
+            let rr: Vec<f64> = case.rr.iter().copied().map(rr_ms).collect();
+            assert_eq!(measurement.rr_intervals, rr, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_measurements() {
+        let cases: [(&str, &[u8]); 5] = [
+            ("empty", &[]),
+            ("8-bit bpm missing", &[0x00]),
+            ("16-bit bpm with 1 byte", &[0x01, 0x48]),
+            ("energy flag with 1 byte left", &[0x08, 0x48, 0x34]),
+            ("16-bit bpm and energy flag with 1 byte left", &[0x09, 0x48, 0x00, 0x34]),
+        ];
+
+        for (name, data) in cases {
+            assert!(parse_heart_rate(data).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn rr_flag_without_intervals_is_empty() {
+        let measurement = parse_heart_rate(&[0x10, 0x48]).unwrap();
+        assert!(measurement.rr_intervals.is_empty());
+    }
+}
@@ -0,0 +1,548 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart::{self, HeartRateSample};
+use crate::settings;
+use crate::summary::SessionSummary;
+
+/// 数据库结构
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        device_id TEXT NOT NULL,
+        label TEXT,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS samples (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+        device_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        bpm INTEGER NOT NULL,
+        rr_intervals TEXT NOT NULL,
+        sensor_contact INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_samples_device_time ON samples (device_id, timestamp);
+    CREATE INDEX IF NOT EXISTS idx_samples_session ON samples (session_id);
+";
+
+/// 数据库升级语句，按顺序执行，已执行的数量记录在 user_version 中
+const MIGRATIONS: &[&str] = &[
+    // 会话统计摘要 (JSON)
+    "ALTER TABLE sessions ADD COLUMN summary TEXT",
+    // 样本所在的心率区间
+    "ALTER TABLE samples ADD COLUMN zone INTEGER",
+];
+
+/// 查询时的聚合粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum HistoryBucket {
+    /// 不聚合，返回原始样本
+    #[serde(rename = "raw")]
+    Raw,
+    #[serde(rename = "1s")]
+    Second,
+    #[serde(rename = "10s")]
+    TenSeconds,
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "1h")]
+    Hour,
+}
+
+impl HistoryBucket {
+    /// 聚合区间长度 (毫秒)，原始样本返回 None
+    fn duration_ms(self) -> Option<u64> {
+        match self {
+            Self::Raw => None,
+            Self::Second => Some(1_000),
+            Self::TenSeconds => Some(10_000),
+            Self::Minute => Some(60_000),
+            Self::Hour => Some(3_600_000),
+        }
+    }
+}
+
+/// 一个聚合区间内的心率统计
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPoint {
+    /// 区间起始时间 (Unix 毫秒)，原始样本为样本时间
+    pub timestamp: u64,
+    pub min: u16,
+    pub max: u16,
+    pub avg: f64,
+    pub count: u32,
+}
+
+/// 区间统计累加器
+struct BucketAccumulator {
+    start: u64,
+    min: u16,
+    max: u16,
+    sum: u64,
+    count: u32,
+}
+
+impl BucketAccumulator {
+    fn new(start: u64, bpm: u16) -> Self {
+        Self {
+            start,
+            min: bpm,
+            max: bpm,
+            sum: bpm as u64,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, bpm: u16) {
+        self.min = self.min.min(bpm);
+        self.max = self.max.max(bpm);
+        self.sum += bpm as u64;
+        self.count += 1;
+    }
+
+    fn finish(self) -> HistoryPoint {
+        HistoryPoint {
+            timestamp: self.start,
+            min: self.min,
+            max: self.max,
+            avg: self.sum as f64 / self.count as f64,
+            count: self.count,
+        }
+    }
+}
+
+/// 发送给写入线程的操作
+enum HistoryCommand {
+    /// 开始会话，收到第一个样本时才写入会话记录
+    Begin {
+        device_id: String,
+        label: Option<String>,
+    },
+    Sample(HeartRateSample),
+    End {
+        device_id: String,
+        ended_at: u64,
+        summary: Option<SessionSummary>,
+    },
+}
+
+/// 设备的会话状态
+enum SessionSlot {
+    /// 已开始但还没有样本，尚未写入数据库
+    Pending { label: Option<String> },
+    Open(i64),
+}
+
+/// 写入线程持有的状态：数据库连接与各设备当前的会话
+struct HistoryWriter {
+    /// 首次写入时打开
+    conn: Option<Connection>,
+    /// 按设备 ID 索引的会话
+    sessions: BTreeMap<String, SessionSlot>,
+}
+
+/// 写入线程的发送端，首次使用时启动线程
+static WRITER: OnceLock<mpsc::UnboundedSender<HistoryCommand>> = OnceLock::new();
+
+fn get_history_path() -> AppResult<PathBuf> {
+    Ok(settings::get_config_dir()?.join("history.db"))
+}
+
+fn storage_error(e: rusqlite::Error) -> AppError {
+    AppError::new(ErrorCode::StorageIo, format!("History database error: {e}"))
+}
+
+/// 打开数据库并补全上次异常退出时未关闭的会话
+fn open_database() -> AppResult<Connection> {
+    let path = get_history_path()?;
+    let conn = Connection::open(&path).map_err(storage_error)?;
+
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(storage_error)?;
+    conn.execute_batch(SCHEMA).map_err(storage_error)?;
+    migrate(&conn)?;
+
+    let closed = conn
+        .execute(
+            "UPDATE sessions SET ended_at = COALESCE(
+                (SELECT MAX(timestamp) FROM samples WHERE samples.session_id = sessions.id),
+                started_at
+            ) WHERE ended_at IS NULL",
+            [],
+        )
+        .map_err(storage_error)?;
+    if closed > 0 {
+        eprintln!("Closed {closed} unfinished history sessions");
+    }
+
+    eprintln!("History database opened: {:?}", path);
+    Ok(conn)
+}
+
+/// 执行尚未执行的升级语句
+fn migrate(conn: &Connection) -> AppResult<()> {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(storage_error)?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(migration).map_err(storage_error)?;
+        conn.pragma_update(None, "user_version", index + 1)
+            .map_err(storage_error)?;
+        eprintln!("History database migrated to version {}", index + 1);
+    }
+
+    Ok(())
+}
+
+fn end_session(
+    conn: &Connection,
+    session_id: i64,
+    ended_at: u64,
+    summary: Option<&SessionSummary>,
+) -> AppResult<()> {
+    let summary = summary.and_then(|summary| serde_json::to_string(summary).ok());
+    conn.execute(
+        "UPDATE sessions SET ended_at = ?1, summary = ?2 WHERE id = ?3",
+        params![ended_at, summary, session_id],
+    )
+    .map_err(storage_error)?;
+    Ok(())
+}
+
+fn insert_session(conn: &Connection, device_id: &str, label: Option<&str>, started_at: u64) -> AppResult<i64> {
+    conn.execute(
+        "INSERT INTO sessions (device_id, label, started_at) VALUES (?1, ?2, ?3)",
+        params![device_id, label, started_at],
+    )
+    .map_err(storage_error)?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn insert_sample(conn: &Connection, session_id: i64, sample: &HeartRateSample) -> AppResult<()> {
+    let measurement = &sample.measurement;
+    let rr_intervals = serde_json::to_string(&measurement.rr_intervals).unwrap_or_else(|_| "[]".to_string());
+    let sensor_contact = measurement
+        .sensor_contact_supported
+        .then_some(measurement.sensor_contact_detected);
+
+    conn.execute(
+        "INSERT INTO samples (session_id, device_id, timestamp, bpm, rr_intervals, sensor_contact, zone)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            session_id,
+            sample.device_id,
+            measurement.timestamp,
+            measurement.bpm,
+            rr_intervals,
+            sensor_contact,
+            sample.zone,
+        ],
+    )
+    .map_err(storage_error)?;
+    Ok(())
+}
+
+impl HistoryWriter {
+    /// 获取数据库连接，必要时打开数据库
+    fn conn(&mut self) -> AppResult<&Connection> {
+        if self.conn.is_none() {
+            self.conn = Some(open_database()?);
+        }
+        Ok(self.conn.as_ref().expect("history database opened"))
+    }
+
+    fn handle(&mut self, command: HistoryCommand) -> AppResult<()> {
+        match command {
+            HistoryCommand::Begin { device_id, label } => {
+                // 设备已有未关闭的会话时先将其关闭
+                if let Some(SessionSlot::Open(previous)) = self.sessions.remove(&device_id) {
+                    end_session(self.conn()?, previous, heart::current_timestamp_millis(), None)?;
+                }
+                self.sessions.insert(device_id, SessionSlot::Pending { label });
+            }
+            HistoryCommand::Sample(sample) => {
+                let session_id = match self.sessions.get(&sample.device_id) {
+                    Some(SessionSlot::Open(session_id)) => *session_id,
+                    Some(SessionSlot::Pending { label }) => {
+                        let label = label.clone();
+                        let session_id = insert_session(
+                            self.conn()?,
+                            &sample.device_id,
+                            label.as_deref(),
+                            sample.measurement.timestamp,
+                        )?;
+                        eprintln!("History session {session_id} opened for device: {}", sample.device_id);
+                        self.sessions
+                            .insert(sample.device_id.clone(), SessionSlot::Open(session_id));
+                        session_id
+                    }
+                    // 设备没有进行中的会话时忽略
+                    None => return Ok(()),
+                };
+                insert_sample(self.conn()?, session_id, &sample)?;
+            }
+            HistoryCommand::End {
+                device_id,
+                ended_at,
+                summary,
+            } => {
+                if let Some(SessionSlot::Open(session_id)) = self.sessions.remove(&device_id) {
+                    end_session(self.conn()?, session_id, ended_at, summary.as_ref())?;
+                    eprintln!("History session {session_id} closed for device: {device_id}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 写入线程：依次执行操作，阻塞的数据库写入不占用异步运行时
+fn run_writer(mut receiver: mpsc::UnboundedReceiver<HistoryCommand>) {
+    let mut writer = HistoryWriter {
+        conn: None,
+        sessions: BTreeMap::new(),
+    };
+
+    while let Some(command) = receiver.blocking_recv() {
+        if let Err(e) = writer.handle(command) {
+            eprintln!("Failed to write history: {e}");
+        }
+    }
+}
+
+fn send(command: HistoryCommand) {
+    let sender = WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        if let Err(e) = std::thread::Builder::new()
+            .name("history-writer".to_string())
+            .spawn(move || run_writer(receiver))
+        {
+            eprintln!("Failed to start history writer: {e}");
+        }
+        sender
+    });
+
+    if sender.send(command).is_err() {
+        eprintln!("History writer is not running");
+    }
+}
+
+/// 开始新会话，收到第一个样本时才写入会话记录，连接失败的会话不会留下空记录
+pub(crate) fn open_session(device_id: &str, label: Option<&str>) {
+    send(HistoryCommand::Begin {
+        device_id: device_id.to_string(),
+        label: label.map(str::to_string),
+    });
+}
+
+/// 关闭设备当前的会话，并保存会话统计摘要
+pub(crate) fn close_session(device_id: &str, summary: Option<&SessionSummary>) {
+    send(HistoryCommand::End {
+        device_id: device_id.to_string(),
+        ended_at: summary.map_or_else(heart::current_timestamp_millis, |summary| summary.ended_at),
+        summary: summary.cloned(),
+    });
+}
+
+/// 写入一个样本（设备没有进行中的会话时忽略）
+pub(crate) fn record_sample(sample: &HeartRateSample) {
+    send(HistoryCommand::Sample(sample.clone()));
+}
+
+/// 按时间顺序读取样本并逐个区间聚合，不会一次性载入全部样本
+fn aggregate_samples(
+    conn: &Connection,
+    device_id: &str,
+    start: u64,
+    end: u64,
+    bucket: HistoryBucket,
+) -> AppResult<Vec<HistoryPoint>> {
+    let mut statement = conn
+        .prepare(
+            "SELECT timestamp, bpm FROM samples
+             WHERE device_id = ?1 AND timestamp >= ?2 AND timestamp < ?3
+             ORDER BY timestamp",
+        )
+        .map_err(storage_error)?;
+    let mut rows = statement
+        .query(params![device_id, start, end])
+        .map_err(storage_error)?;
+
+    let size = bucket.duration_ms();
+    let mut points = Vec::new();
+    let mut current: Option<BucketAccumulator> = None;
+
+    while let Some(row) = rows.next().map_err(storage_error)? {
+        let timestamp: u64 = row.get(0).map_err(storage_error)?;
+        let bpm: u16 = row.get(1).map_err(storage_error)?;
+
+        let bucket_start = match size {
+            Some(size) => timestamp - timestamp % size,
+            None => timestamp,
+        };
+
+        match current.as_mut() {
+            Some(acc) if size.is_some() && acc.start == bucket_start => acc.add(bpm),
+            _ => {
+                points.extend(current.take().map(BucketAccumulator::finish));
+                current = Some(BucketAccumulator::new(bucket_start, bpm));
+            }
+        }
+    }
+    points.extend(current.map(BucketAccumulator::finish));
+
+    Ok(points)
+}
+
+/// 以只读方式打开数据库，与写入连接互不阻塞（WAL 模式允许并发读），数据库不存在时返回 None
+fn open_reader() -> AppResult<Option<Connection>> {
+    let path = get_history_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map(Some)
+        .map_err(storage_error)
+}
+
+/// 查询设备在 [start, end) 时间范围内的心率历史，按 `bucket` 聚合为最小、最大、平均值与样本数。
+/// 查询使用独立的只读连接并在阻塞线程中执行，不影响实时样本的写入
+#[tauri::command]
+pub async fn query_history(
+    device_id: Option<String>,
+    start: u64,
+    end: u64,
+    bucket: HistoryBucket,
+) -> AppResult<Vec<HistoryPoint>> {
+    if end <= start {
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "End of the time range must be after its start",
+        ));
+    }
+
+    let device_id = heart::resolve_device_id(device_id).await?;
+
+    tokio::task::spawn_blocking(move || match open_reader()? {
+        Some(conn) => aggregate_samples(&conn, &device_id, start, end, bucket),
+        None => Ok(Vec::new()),
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::StorageIo, format!("History query failed: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: &[(&str, u64, u16)] = &[
+        ("band", 0, 60),
+        ("band", 999, 62),
+        ("band", 1_000, 70),
+        ("band", 9_999, 80),
+        ("band", 10_000, 90),
+        ("band", 59_999, 100),
+        ("band", 60_000, 110),
+        ("band", 3_599_999, 120),
+        ("band", 3_600_000, 130),
+        ("other", 500, 180),
+        ("other", 10_500, 190),
+    ];
+
+    fn database(samples: &[(&str, u64, u16)]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn.execute("INSERT INTO sessions (device_id, started_at) VALUES ('band', 0)", [])
+            .unwrap();
+        for (device_id, timestamp, bpm) in samples {
+            conn.execute(
+                "INSERT INTO samples (session_id, device_id, timestamp, bpm, rr_intervals)
+                 VALUES (1, ?1, ?2, ?3, '[]')",
+                params![device_id, timestamp, bpm],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    /// 各区间的起始时间与样本数
+    fn buckets(conn: &Connection, device_id: &str, start: u64, end: u64, bucket: HistoryBucket) -> Vec<(u64, u32)> {
+        aggregate_samples(conn, device_id, start, end, bucket)
+            .unwrap()
+            .into_iter()
+            .map(|point| (point.timestamp, point.count))
+            .collect()
+    }
+
+    #[test]
+    fn aggregates_into_bucket_boundaries() {
+        let conn = database(SAMPLES);
+        let query = |bucket| buckets(&conn, "band", 0, 4_000_000, bucket);
+
+        assert_eq!(
+            query(HistoryBucket::Second),
+            [(0, 2), (1_000, 1), (9_000, 1), (10_000, 1), (59_000, 1), (60_000, 1), (3_599_000, 1), (3_600_000, 1)]
+        );
+        assert_eq!(
+            query(HistoryBucket::TenSeconds),
+            [(0, 4), (10_000, 1), (50_000, 1), (60_000, 1), (3_590_000, 1), (3_600_000, 1)]
+        );
+        assert_eq!(
+            query(HistoryBucket::Minute),
+            [(0, 6), (60_000, 1), (3_540_000, 1), (3_600_000, 1)]
+        );
+        assert_eq!(query(HistoryBucket::Hour), [(0, 8), (3_600_000, 1)]);
+    }
+
+    #[test]
+    fn computes_bucket_statistics() {
+        let conn = database(SAMPLES);
+        let points = aggregate_samples(&conn, "band", 0, 60_000, HistoryBucket::Minute).unwrap();
+
+        assert_eq!(points.len(), 1);
+        assert_eq!((points[0].min, points[0].max), (60, 100));
+        assert_eq!(points[0].avg, 77.0);
+    }
+
+    #[test]
+    fn raw_returns_every_sample() {
+        let conn = database(&[("band", 500, 70), ("band", 500, 72), ("band", 1_500, 75)]);
+        let points = aggregate_samples(&conn, "band", 0, 2_000, HistoryBucket::Raw).unwrap();
+
+        let bpm: Vec<(u64, u16, u32)> = points.iter().map(|p| (p.timestamp, p.min, p.count)).collect();
+        assert_eq!(bpm, [(500, 70, 1), (500, 72, 1), (1_500, 75, 1)]);
+    }
+
+    #[test]
+    fn end_of_range_is_exclusive() {
+        let conn = database(SAMPLES);
+
+        assert_eq!(
+            buckets(&conn, "band", 1_000, 10_000, HistoryBucket::Raw),
+            [(1_000, 1), (9_999, 1)]
+        );
+        assert_eq!(buckets(&conn, "band", 1_000, 10_001, HistoryBucket::Raw).len(), 3);
+    }
+
+    #[test]
+    fn filters_by_device() {
+        let conn = database(SAMPLES);
+
+        assert_eq!(
+            buckets(&conn, "other", 0, 4_000_000, HistoryBucket::TenSeconds),
+            [(0, 1), (10_000, 1)]
+        );
+        assert_eq!(buckets(&conn, "band", 0, 1_000, HistoryBucket::Second), [(0, 2)]);
+        assert!(buckets(&conn, "missing", 0, 4_000_000, HistoryBucket::Raw).is_empty());
+    }
+}
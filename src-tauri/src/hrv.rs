@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::heart::{self, HeartRateSample};
+use crate::settings::{self, HrvSettings};
+
+/// 有效 RR 间期范围 (毫秒)，超出视为伪差丢弃
+const MIN_RR_MS: f64 = 300.0;
+const MAX_RR_MS: f64 = 2000.0;
+/// 计算指标所需的最少 RR 间期数
+const MIN_RR_COUNT: u32 = 10;
+/// 压力指数直方图的分组宽度 (毫秒)
+const HISTOGRAM_BIN_MS: f64 = 50.0;
+
+/// 心率变异性指标
+#[derive(Debug, Clone, Serialize)]
+pub struct HrvMetrics {
+    /// 参与计算的 RR 间期数
+    pub rr_count: u32,
+    /// 平均 RR 间期 (毫秒)
+    pub mean_rr: f64,
+    /// RR 间期标准差 (毫秒)
+    pub sdnn: f64,
+    /// 相邻 RR 间期差值的均方根 (毫秒)
+    pub rmssd: f64,
+    /// 相邻 RR 间期差值超过 50ms 的比例 (%)
+    pub pnn50: f64,
+    /// Baevsky 压力指数，RR 间期无波动时为空
+    pub stress_index: Option<f64>,
+}
+
+/// HRV 更新事件
+#[derive(Debug, Clone, Serialize)]
+pub struct HrvUpdate {
+    pub device_id: String,
+    /// 滑动窗口长度 (秒)
+    pub window_secs: u64,
+    #[serde(flatten)]
+    pub metrics: HrvMetrics,
+    pub timestamp: u64,
+}
+
+/// HRV 累加器，逐个加入 RR 间期
+#[derive(Default)]
+struct HrvAccumulator {
+    count: u32,
+    /// Welford 算法的均值与离差平方和
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+    /// 上一个 RR 间期，序列中断后为空
+    previous: Option<f64>,
+    diff_count: u32,
+    diff_square_sum: f64,
+    nn50_count: u32,
+    /// 按 50ms 分组的直方图
+    histogram: BTreeMap<i64, u32>,
+}
+
+impl HrvAccumulator {
+    fn push(&mut self, rr: f64) {
+        self.count += 1;
+        let delta = rr - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (rr - self.mean);
+
+        if self.count == 1 {
+            self.min = rr;
+            self.max = rr;
+        } else {
+            self.min = self.min.min(rr);
+            self.max = self.max.max(rr);
+        }
+
+        if let Some(previous) = self.previous {
+            let diff = rr - previous;
+            self.diff_count += 1;
+            self.diff_square_sum += diff * diff;
+            if diff.abs() > 50.0 {
+                self.nn50_count += 1;
+            }
+        }
+        self.previous = Some(rr);
+
+        *self.histogram.entry((rr / HISTOGRAM_BIN_MS).floor() as i64).or_default() += 1;
+    }
+
+    /// 数据中断（如断线重连）后不再与之前的间期计算逐差
+    fn break_sequence(&mut self) {
+        self.previous = None;
+    }
+
+    /// Baevsky 压力指数：SI = AMo / (2 × Mo × MxDMn)，
+    /// AMo 为众数组占比 (%)，Mo 为众数 (秒)，MxDMn 为极差 (秒)
+    fn stress_index(&self) -> Option<f64> {
+        let (&bin, &mode_count) = self
+            .histogram
+            .iter()
+            .max_by_key(|(bin, count)| (**count, std::cmp::Reverse(**bin)))?;
+
+        let amplitude = mode_count as f64 / self.count as f64 * 100.0;
+        let mode = (bin as f64 + 0.5) * HISTOGRAM_BIN_MS / 1000.0;
+        let range = (self.max - self.min) / 1000.0;
+
+        (range > 0.0).then(|| amplitude / (2.0 * mode * range))
+    }
+
+    fn metrics(&self) -> Option<HrvMetrics> {
+        if self.count < MIN_RR_COUNT || self.diff_count == 0 {
+            return None;
+        }
+
+        Some(HrvMetrics {
+            rr_count: self.count,
+            mean_rr: self.mean,
+            sdnn: (self.m2 / (self.count - 1) as f64).sqrt(),
+            rmssd: (self.diff_square_sum / self.diff_count as f64).sqrt(),
+            pnn50: self.nn50_count as f64 / self.diff_count as f64 * 100.0,
+            stress_index: self.stress_index(),
+        })
+    }
+}
+
+/// 单个心率流的 HRV 跟踪
+struct HrvTracker {
+    settings: HrvSettings,
+    /// 滑动窗口内的 RR 间期及其样本时间戳
+    window: VecDeque<(u64, f64)>,
+    /// 整个会话的累加器
+    session: HrvAccumulator,
+    last_sample_at: Option<u64>,
+    last_emit_at: Option<u64>,
+}
+
+impl HrvTracker {
+    fn new(settings: HrvSettings) -> Self {
+        Self {
+            settings,
+            window: VecDeque::new(),
+            session: HrvAccumulator::default(),
+            last_sample_at: None,
+            last_emit_at: None,
+        }
+    }
+
+    fn record(&mut self, timestamp: u64, rr_intervals: &[f64]) {
+        // 数据中断时不计算跨越间隔的逐差
+        if self
+            .last_sample_at
+            .is_some_and(|last| timestamp.saturating_sub(last) > heart::MAX_SAMPLE_GAP_MS)
+        {
+            self.session.break_sequence();
+            self.window.push_back((timestamp, f64::NAN));
+        }
+        self.last_sample_at = Some(timestamp);
+
+        for &rr in rr_intervals.iter().filter(|rr| (MIN_RR_MS..=MAX_RR_MS).contains(*rr)) {
+            self.session.push(rr);
+            self.window.push_back((timestamp, rr));
+        }
+
+        let window_start = timestamp.saturating_sub(self.settings.window_secs.saturating_mul(1000));
+        while self.window.front().is_some_and(|(at, _)| *at < window_start) {
+            self.window.pop_front();
+        }
+    }
+
+    /// 滑动窗口内的指标，窗口内的中断标记（NaN）会断开逐差序列
+    fn window_metrics(&self) -> Option<HrvMetrics> {
+        let mut accumulator = HrvAccumulator::default();
+        for &(_, rr) in &self.window {
+            if rr.is_nan() {
+                accumulator.break_sequence();
+            } else {
+                accumulator.push(rr);
+            }
+        }
+        accumulator.metrics()
+    }
+
+    /// 距上次更新已超过更新间隔时返回窗口指标
+    fn due_update(&mut self, timestamp: u64) -> Option<HrvMetrics> {
+        let interval = self.settings.update_interval_secs.saturating_mul(1000);
+        if interval == 0 {
+            return None;
+        }
+        if self
+            .last_emit_at
+            .is_some_and(|last| timestamp.saturating_sub(last) < interval)
+        {
+            return None;
+        }
+
+        let metrics = self.window_metrics()?;
+        self.last_emit_at = Some(timestamp);
+        Some(metrics)
+    }
+}
+
+/// 按设备 ID 索引的 HRV 跟踪
+static HRV_TRACKERS: Mutex<BTreeMap<String, HrvTracker>> = Mutex::const_new(BTreeMap::new());
+
+/// 开始计算设备的 HRV
+pub(crate) async fn begin(device_id: &str) {
+    let settings = settings::load_settings().map(|s| s.hrv).unwrap_or_default();
+    HRV_TRACKERS
+        .lock()
+        .await
+        .insert(device_id.to_string(), HrvTracker::new(settings));
+}
+
+/// 计入样本中的 RR 间期，按设置的间隔发送 "hrv-update" 事件
+pub(crate) async fn record_sample(app: &AppHandle, sample: &HeartRateSample) {
+    let measurement = &sample.measurement;
+    if measurement.rr_intervals.is_empty() {
+        return;
+    }
+
+    let mut trackers = HRV_TRACKERS.lock().await;
+    let Some(tracker) = trackers.get_mut(&sample.device_id) else {
+        return;
+    };
+
+    tracker.record(measurement.timestamp, &measurement.rr_intervals);
+
+    if let Some(metrics) = tracker.due_update(measurement.timestamp) {
+        let _ = app.emit(
+            "hrv-update",
+            HrvUpdate {
+                device_id: sample.device_id.clone(),
+                window_secs: tracker.settings.window_secs,
+                metrics,
+                timestamp: measurement.timestamp,
+            },
+        );
+    }
+}
+
+/// 结束计算并返回整个会话的 HRV 指标，RR 间期不足时为空
+pub(crate) async fn finish(device_id: &str) -> Option<HrvMetrics> {
+    let tracker = HRV_TRACKERS.lock().await.remove(device_id)?;
+    tracker.session.metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 均值 835ms，逐差为 ±50ms 与 ±100ms 交替
+    const SERIES: [f64; 10] = [800.0, 850.0, 800.0, 900.0, 800.0, 850.0, 800.0, 900.0, 800.0, 850.0];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    fn tracker(window_secs: u64) -> HrvTracker {
+        HrvTracker::new(HrvSettings {
+            window_secs,
+            update_interval_secs: 5,
+        })
+    }
+
+    #[test]
+    fn computes_metrics_from_rr_series() {
+        let mut accumulator = HrvAccumulator::default();
+        SERIES.iter().for_each(|rr| accumulator.push(*rr));
+        let metrics = accumulator.metrics().expect("enough RR intervals");
+
+        assert_eq!(metrics.rr_count, 10);
+        assert_close(metrics.mean_rr, 835.0);
+        // 离差平方和：5 × 35² + 3 × 15² + 2 × 65² = 15250
+        assert_close(metrics.sdnn, (15250.0_f64 / 9.0).sqrt());
+        // 9 个逐差：5 个 ±50、4 个 ±100
+        assert_close(metrics.rmssd, (52500.0_f64 / 9.0).sqrt());
+        // 只有 ±100 超过 50ms
+        assert_close(metrics.pnn50, 4.0 / 9.0 * 100.0);
+        // 众数组 [800, 850) 占 50%，组中值 0.825s，极差 0.1s
+        assert_close(metrics.stress_index.unwrap(), 50.0 / (2.0 * 0.825 * 0.1));
+    }
+
+    #[test]
+    fn requires_enough_rr_intervals() {
+        let mut accumulator = HrvAccumulator::default();
+        SERIES[..9].iter().for_each(|rr| accumulator.push(*rr));
+        assert!(accumulator.metrics().is_none());
+    }
+
+    #[test]
+    fn constant_rr_has_no_stress_index() {
+        let mut accumulator = HrvAccumulator::default();
+        (0..10).for_each(|_| accumulator.push(800.0));
+        let metrics = accumulator.metrics().unwrap();
+
+        assert_close(metrics.sdnn, 0.0);
+        assert_close(metrics.rmssd, 0.0);
+        assert!(metrics.stress_index.is_none());
+    }
+
+    #[test]
+    fn evicts_samples_outside_window() {
+        let mut tracker = tracker(5);
+        for second in 0..10 {
+            tracker.record(second * 1000, &[800.0]);
+        }
+
+        // 最后一个样本在 9s，窗口从 4s 开始
+        assert_eq!(tracker.window.len(), 6);
+        assert_eq!(tracker.window.front().map(|(at, _)| *at), Some(4000));
+        assert_eq!(tracker.session.count, 10);
+    }
+
+    #[test]
+    fn breaks_sequence_across_sample_gap() {
+        let mut tracker = tracker(300);
+        for second in 0..10 {
+            tracker.record(second * 1000, &[800.0]);
+        }
+        for second in 15..25 {
+            tracker.record(second * 1000, &[1000.0]);
+        }
+
+        assert_eq!(tracker.window.iter().filter(|(_, rr)| rr.is_nan()).count(), 1);
+
+        // 跨越间隔的 200ms 逐差不计入
+        for metrics in [tracker.session.metrics().unwrap(), tracker.window_metrics().unwrap()] {
+            assert_eq!(metrics.rr_count, 20);
+            assert_close(metrics.mean_rr, 900.0);
+            assert_close(metrics.rmssd, 0.0);
+            assert_close(metrics.pnn50, 0.0);
+        }
+    }
+
+    #[test]
+    fn filters_rr_outside_valid_range() {
+        let mut tracker = tracker(60);
+        tracker.record(0, &[250.0, 300.0, 800.0, 2000.0, 2100.0]);
+
+        let kept: Vec<f64> = tracker.window.iter().map(|(_, rr)| *rr).collect();
+        assert_eq!(kept, [300.0, 800.0, 2000.0]);
+        assert_eq!(tracker.session.count, 3);
+    }
+
+    #[test]
+    fn large_settings_do_not_overflow() {
+        let mut tracker = HrvTracker::new(HrvSettings {
+            window_secs: u64::MAX,
+            update_interval_secs: u64::MAX,
+        });
+        for second in 0..10 {
+            tracker.record(second * 1000, &SERIES);
+        }
+
+        assert!(tracker.due_update(9000).is_some());
+        assert!(tracker.due_update(10_000).is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart::{self, DeviceInfo};
+use crate::settings;
+
+/// 自动连接时等待适配器可用的间隔
+const AUTO_CONNECT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 已知设备
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownDevice {
+    pub id: String,
+    pub mac_address: Option<String>,
+    pub name: Option<String>,
+    /// 最后一次连接时间 (Unix 毫秒)
+    pub last_seen: u64,
+    pub nickname: Option<String>,
+    /// 固定为自动连接设备
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// 串行化已知设备文件的读写
+static KNOWN_DEVICES_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn get_known_devices_path() -> AppResult<PathBuf> {
+    Ok(settings::get_config_dir()?.join("known_devices.json"))
+}
+
+/// 读取已知设备列表，文件不存在时为空，读取或解析失败时返回 `StorageIo`
+fn read_known_devices() -> AppResult<Vec<KnownDevice>> {
+    let path = get_known_devices_path()?;
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        AppError::new(ErrorCode::StorageIo, format!("Failed to read known devices file: {}", e))
+    })?;
+    serde_json::from_str(&content).map_err(|e| {
+        AppError::new(ErrorCode::StorageIo, format!("Failed to parse known devices: {}", e))
+    })
+}
+
+/// 读取已知设备列表用于自动连接，失败时记录日志并返回空列表
+fn load_known_devices() -> Vec<KnownDevice> {
+    read_known_devices().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        Vec::new()
+    })
+}
+
+/// 先写入临时文件再重命名，写入中断时不会留下半个文件
+fn save_known_devices(devices: &[KnownDevice]) -> AppResult<()> {
+    let path = get_known_devices_path()?;
+    let temp_path = path.with_extension("json.tmp");
+
+    let content = serde_json::to_string_pretty(devices).map_err(|e| {
+        AppError::new(ErrorCode::StorageIo, format!("Failed to serialize known devices: {}", e))
+    })?;
+    fs::write(&temp_path, content).map_err(|e| {
+        AppError::new(ErrorCode::StorageIo, format!("Failed to write known devices file: {}", e))
+    })?;
+    fs::rename(&temp_path, &path).map_err(|e| {
+        AppError::new(ErrorCode::StorageIo, format!("Failed to replace known devices file: {}", e))
+    })
+}
+
+/// 读取、修改并保存已知设备列表。
+/// 文件无法读取时返回错误而不是覆盖为空列表
+async fn update_known_devices<T>(
+    update: impl FnOnce(&mut Vec<KnownDevice>) -> AppResult<T>,
+) -> AppResult<T> {
+    let _guard = KNOWN_DEVICES_LOCK.lock().await;
+    let mut devices = read_known_devices()?;
+    let result = update(&mut devices)?;
+    save_known_devices(&devices)?;
+    Ok(result)
+}
+
+/// 记录成功连接的设备
+pub(crate) async fn remember_device(info: DeviceInfo) {
+    let result = update_known_devices(|devices| {
+        let now = heart::current_timestamp_millis();
+        match devices.iter_mut().find(|d| d.id == info.id) {
+            Some(device) => {
+                device.mac_address = info.mac_address.or(device.mac_address.take());
+                device.name = info.name.or(device.name.take());
+                device.last_seen = now;
+            }
+            None => devices.push(KnownDevice {
+                id: info.id,
+                mac_address: info.mac_address,
+                name: info.name,
+                last_seen: now,
+                nickname: None,
+                pinned: false,
+            }),
+        }
+        Ok(())
+    })
+    .await;
+
+    if let Err(e) = result {
+        eprintln!("Failed to remember device: {e}");
+    }
+}
+
+/// 自动连接的目标设备：优先固定设备，否则最近连接的设备
+fn auto_connect_target(devices: &[KnownDevice]) -> Option<&KnownDevice> {
+    devices
+        .iter()
+        .find(|d| d.pinned)
+        .or_else(|| devices.iter().max_by_key(|d| d.last_seen))
+}
+
+/// 启动时自动连接已知设备，设备出现前心率流保持在 Scanning 状态持续扫描
+pub fn init_auto_connect(app: &AppHandle) {
+    let enabled = settings::load_settings()
+        .map(|s| s.auto_connect)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(target) = auto_connect_target(&load_known_devices()).cloned() else {
+            eprintln!("Auto-connect enabled but no known devices");
+            return;
+        };
+
+        eprintln!("Auto-connecting to device: {}", target.id);
+
+        // 等待适配器可用后只启动一次，设备不在范围内时由心率流自行扫描
+        while heart::get_adapter().await.is_err() {
+            tokio::time::sleep(AUTO_CONNECT_RETRY_INTERVAL).await;
+        }
+
+        match heart::start_heart_rate_stream_when_found(app, target.id.clone(), target.nickname.clone()).await {
+            Ok(_) => eprintln!("Auto-connect started stream for device: {}", target.id),
+            Err(e) => eprintln!("Auto-connect failed: {e}"),
+        }
+    });
+}
+
+/// 获取已知设备列表（最近连接的在前）
+#[tauri::command]
+pub async fn get_known_devices() -> AppResult<Vec<KnownDevice>> {
+    let _guard = KNOWN_DEVICES_LOCK.lock().await;
+    let mut devices = read_known_devices()?;
+    devices.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+    Ok(devices)
+}
+
+/// 设置已知设备昵称，传入空值则清除
+#[tauri::command]
+pub async fn set_known_device_nickname(id: String, nickname: Option<String>) -> AppResult<KnownDevice> {
+    update_known_devices(|devices| {
+        let device = devices
+            .iter_mut()
+            .find(|d| d.id == id)
+            .ok_or_else(|| AppError::new(ErrorCode::DeviceNotFound, format!("Unknown device: {id}")))?;
+        device.nickname = nickname.filter(|n| !n.trim().is_empty());
+        Ok(device.clone())
+    })
+    .await
+}
+
+/// 固定或取消固定自动连接设备，同一时间只允许固定一个设备
+#[tauri::command]
+pub async fn pin_known_device(id: String, pinned: bool) -> AppResult<()> {
+    update_known_devices(|devices| {
+        if !devices.iter().any(|d| d.id == id) {
+            return Err(AppError::new(ErrorCode::DeviceNotFound, format!("Unknown device: {id}")));
+        }
+        for device in devices.iter_mut() {
+            device.pinned = pinned && device.id == id;
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 移除已知设备
+#[tauri::command]
+pub async fn forget_known_device(id: String) -> AppResult<()> {
+    update_known_devices(|devices| {
+        devices.retain(|d| d.id != id);
+        Ok(())
+    })
+    .await
+}
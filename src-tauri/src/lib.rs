@@ -2,10 +2,26 @@ use std::env;
 
 use crate::system::{init_splash, init_tray};
 
+mod adapter;
+mod advertisement;
+mod capture;
+mod connection;
+mod diagnostics;
+mod error;
+mod gatt;
 mod heart;
+mod history;
+mod hrv;
+mod known_devices;
+mod scan;
 mod settings;
+mod signal;
+mod simulation;
+mod source;
+mod summary;
 mod system;
 mod window;
+mod zone;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -26,15 +42,38 @@ pub fn run() {
             let app_handle = app.handle().clone();
             init_tray(&app_handle).expect("init tray failed");
             init_splash(&app_handle).expect("init splash failed");
+            adapter::init_adapter_watcher(&app_handle);
+            known_devices::init_auto_connect(&app_handle);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            adapter::list_adapters,
+            advertisement::start_advertisement_listening,
+            advertisement::stop_advertisement_listening,
+            capture::start_capture,
+            capture::stop_capture,
+            capture::start_replay,
+            connection::get_connection_state,
+            diagnostics::bluetooth_diagnostics,
+            gatt::inspect_device,
             heart::bluetooth_available,
             heart::list_devices,
             heart::select_device,
+            heart::set_device_label,
+            heart::get_stream_status,
             heart::start_heart_rate_stream,
             heart::stop_heart_rate_stream,
             heart::is_heart_rate_streaming,
+            heart::get_device_battery,
+            heart::get_device_details,
+            heart::reset_energy_expended,
+            history::query_history,
+            known_devices::get_known_devices,
+            known_devices::set_known_device_nickname,
+            known_devices::pin_known_device,
+            known_devices::forget_known_device,
+            scan::start_scan,
+            scan::stop_scan,
             settings::get_settings,
             settings::set_settings,
             settings::reset_to_default,
@@ -42,6 +81,7 @@ pub fn run() {
             system::hide_window,
             system::minimize_to_tray,
             window::disable_window_operations,
+            zone::get_heart_rate_zones,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
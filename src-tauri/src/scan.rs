@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bluest::btuuid::{bluetooth_uuid_from_u16, bluetooth_uuid_from_u32};
+use bluest::Uuid;
+use futures_lite::stream::StreamExt;
+use regex::Regex;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart::{self, DeviceInfo};
+use crate::settings::{self, ScanFilter};
+
+/// 信号强度变化超过该值 (dBm) 才发送更新事件
+const RSSI_UPDATE_THRESHOLD: i16 = 3;
+
+/// 后台扫描任务
+static SCAN_TASK: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::const_new(None);
+
+/// 解析后的扫描过滤条件
+pub(crate) struct DeviceFilter {
+    pub(crate) services: Vec<Uuid>,
+    name_pattern: Option<Regex>,
+    min_rssi: Option<i16>,
+    pub(crate) max_devices: usize,
+}
+
+impl DeviceFilter {
+    pub(crate) fn from_settings(filter: &ScanFilter) -> AppResult<Self> {
+        let services = filter
+            .service_uuids
+            .iter()
+            .map(|uuid| parse_uuid(uuid))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let name_pattern = filter
+            .name_pattern
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| AppError::new(ErrorCode::InvalidArgument, format!("Invalid name pattern: {e}")))?;
+
+        Ok(Self {
+            services,
+            name_pattern,
+            min_rssi: filter.min_rssi,
+            max_devices: filter.max_devices,
+        })
+    }
+
+    /// 读取设置中的过滤条件
+    pub(crate) fn load() -> AppResult<Self> {
+        let filter = settings::load_settings()?.scan_filter;
+        Self::from_settings(&filter)
+    }
+
+    /// 设备是否满足名称与信号强度条件
+    pub(crate) fn matches(&self, info: &DeviceInfo) -> bool {
+        let name_matches = self.name_pattern.as_ref().is_none_or(|pattern| {
+            info.name.as_deref().is_some_and(|name| pattern.is_match(name))
+        });
+        // 无法获取信号强度的设备（如已连接设备）不按信号过滤
+        let rssi_matches = match (self.min_rssi, info.rssi) {
+            (Some(min), Some(rssi)) => rssi >= min,
+            _ => true,
+        };
+        name_matches && rssi_matches
+    }
+}
+
+/// 解析服务 UUID，支持 16 位、32 位短格式与完整格式
+fn parse_uuid(value: &str) -> AppResult<Uuid> {
+    let value = value.trim().trim_start_matches("0x").trim_start_matches("0X");
+    let invalid = || AppError::new(ErrorCode::InvalidArgument, format!("Invalid service UUID: {value}"));
+    // from_str_radix 接受 "+" 前缀，短格式只允许十六进制数字
+    let is_hex = value.chars().all(|c| c.is_ascii_hexdigit());
+    match value.len() {
+        4 | 8 if !is_hex => Err(invalid()),
+        4 => u16::from_str_radix(value, 16)
+            .map(bluetooth_uuid_from_u16)
+            .map_err(|_| invalid()),
+        8 => u32::from_str_radix(value, 16)
+            .map(bluetooth_uuid_from_u32)
+            .map_err(|_| invalid()),
+        _ => Uuid::parse_str(value).map_err(|_| invalid()),
+    }
+}
+
+/// 名称或信号强度是否发生了值得通知的变化
+fn has_changed(previous: &DeviceInfo, current: &DeviceInfo) -> bool {
+    let name_changed = current.name.is_some() && current.name != previous.name;
+    let rssi_changed = match (previous.rssi, current.rssi) {
+        (Some(old), Some(new)) => (new - old).abs() >= RSSI_UPDATE_THRESHOLD,
+        (None, Some(_)) => true,
+        _ => false,
+    };
+    name_changed || rssi_changed
+}
+
+/// 开始扫描，每发现一个设备发送 "device-discovered" 事件，
+/// 名称或信号强度变化时发送 "device-updated" 事件。
+/// `duration_ms` 为空时持续扫描，直到调用 `stop_scan`
+#[tauri::command]
+pub async fn start_scan(app: AppHandle, duration_ms: Option<u64>) -> AppResult<()> {
+    let mut task = SCAN_TASK.lock().await;
+
+    if task.as_ref().is_some_and(|t| !t.is_finished()) {
+        return Err(AppError::new(ErrorCode::InvalidState, "Scan is already running"));
+    }
+
+    let filter = DeviceFilter::load()?;
+    let adapter = heart::get_adapter().await?;
+
+    eprintln!("Starting incremental device discovery (duration: {:?} ms)", duration_ms);
+
+    *task = Some(tokio::task::spawn(async move {
+        let mut seen: HashMap<String, DeviceInfo> = HashMap::new();
+
+        // 已连接的设备直接上报
+        let mut connected_ids = HashSet::new();
+        for info in heart::collect_connected_devices(&adapter, &mut connected_ids, &filter).await {
+            let _ = app.emit("device-discovered", &info);
+            seen.insert(info.id.clone(), info);
+        }
+
+        let scan_devices = async {
+            let mut scan = match adapter.scan(&filter.services).await {
+                Ok(scan) => scan,
+                Err(e) => {
+                    let error = AppError::from(e);
+                    eprintln!("Failed to start device discovery: {error}");
+                    let _ = app.emit("scan-error", error);
+                    return;
+                }
+            };
+
+            while let Some(advertising_device) = scan.next().await {
+                let info = heart::advertising_device_to_info(&advertising_device);
+                if !filter.matches(&info) {
+                    continue;
+                }
+
+                match seen.get(&info.id) {
+                    None if seen.len() >= filter.max_devices => continue,
+                    None => {
+                        let _ = app.emit("device-discovered", &info);
+                    }
+                    Some(previous) if has_changed(previous, &info) => {
+                        let _ = app.emit("device-updated", &info);
+                    }
+                    Some(_) => continue,
+                }
+
+                seen.insert(info.id.clone(), info);
+            }
+        };
+
+        match duration_ms {
+            Some(ms) => {
+                let _ = tokio::time::timeout(Duration::from_millis(ms), scan_devices).await;
+            }
+            None => scan_devices.await,
+        }
+
+        eprintln!("Discovery completed. Found {} devices", seen.len());
+        let _ = app.emit("scan-stopped", ());
+    }));
+
+    Ok(())
+}
+
+/// 取消正在进行的扫描
+#[tauri::command]
+pub async fn stop_scan(app: AppHandle) -> AppResult<()> {
+    let mut task = SCAN_TASK.lock().await;
+
+    match task.take() {
+        Some(task) if !task.is_finished() => {
+            task.abort();
+            eprintln!("Device discovery cancelled");
+            let _ = app.emit("scan-stopped", ());
+            Ok(())
+        }
+        _ => Err(AppError::new(ErrorCode::InvalidState, "No scan is running")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HRS: &str = "0000180d-0000-1000-8000-00805f9b34fb";
+
+    fn device(name: Option<&str>, rssi: Option<i16>) -> DeviceInfo {
+        DeviceInfo {
+            id: "device".to_string(),
+            mac_address: None,
+            name: name.map(str::to_string),
+            connected: false,
+            rssi,
+        }
+    }
+
+    fn filter(name_pattern: Option<&str>, min_rssi: Option<i16>) -> DeviceFilter {
+        DeviceFilter::from_settings(&ScanFilter {
+            name_pattern: name_pattern.map(str::to_string),
+            min_rssi,
+            ..ScanFilter::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_short_and_full_uuids() {
+        let hrs = Uuid::parse_str(HRS).unwrap();
+        for value in ["180D", "180d", " 180D ", "0x180D", "0X180d", "0000180D", "0x0000180d", HRS] {
+            assert_eq!(parse_uuid(value).unwrap(), hrs, "{value}");
+        }
+        assert_eq!(
+            parse_uuid("12345678").unwrap(),
+            Uuid::parse_str("12345678-0000-1000-8000-00805f9b34fb").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_uuids() {
+        for value in ["", "0x", "180", "18G0", "+18D", "-18D", "1234567Z", "0000180d-0000-1000-8000"] {
+            let error = parse_uuid(value).unwrap_err();
+            assert_eq!(error.code, ErrorCode::InvalidArgument, "{value}");
+        }
+    }
+
+    #[test]
+    fn filters_by_name_pattern() {
+        let mi = filter(Some("^Mi "), None);
+        assert!(mi.matches(&device(Some("Mi Band 5"), Some(-60))));
+        assert!(!mi.matches(&device(Some("Amazfit"), Some(-60))));
+        // 设置了名称条件时，没有名称的设备不匹配
+        assert!(!mi.matches(&device(None, Some(-60))));
+
+        let any = filter(Some(""), None);
+        assert!(any.matches(&device(None, None)));
+    }
+
+    #[test]
+    fn filters_by_rssi_floor() {
+        let nearby = filter(None, Some(-70));
+        assert!(nearby.matches(&device(None, Some(-70))));
+        assert!(!nearby.matches(&device(None, Some(-71))));
+        // 没有信号强度的设备不按信号过滤
+        assert!(nearby.matches(&device(None, None)));
+    }
+
+    #[test]
+    fn rejects_invalid_name_pattern() {
+        let result = DeviceFilter::from_settings(&ScanFilter {
+            name_pattern: Some("(".to_string()),
+            ..ScanFilter::default()
+        });
+        assert_eq!(result.err().map(|e| e.code), Some(ErrorCode::InvalidArgument));
+    }
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::heart;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FloatingWindowSettings {
@@ -9,6 +13,62 @@ pub struct FloatingWindowSettings {
     pub auto_start: bool,
     pub show_device_name: bool,
     pub animation_speed: String,
+    /// 低电量提醒阈值 (%)
+    #[serde(default = "default_low_battery_threshold")]
+    pub low_battery_threshold: u8,
+    /// 超过该时间 (秒) 未收到心率数据视为信号中断，为 0 时关闭检测
+    #[serde(default = "default_stale_timeout_secs")]
+    pub stale_timeout_secs: u64,
+    /// 用户资料，用于计算心率区间
+    #[serde(default)]
+    pub profile: UserProfile,
+    /// 开始心率流时重置设备的累计能量消耗
+    #[serde(default = "default_reset_energy_on_start")]
+    pub reset_energy_on_start: bool,
+    /// 心率变异性计算参数
+    #[serde(default)]
+    pub hrv: HrvSettings,
+    /// 会话统计中计算心率达到各阈值 (bpm) 以上的时长
+    #[serde(default = "default_summary_thresholds")]
+    pub summary_thresholds: Vec<u16>,
+    /// 断线重连策略
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    /// 启动时自动连接已知设备
+    #[serde(default)]
+    pub auto_connect: bool,
+    /// 设备扫描过滤条件
+    #[serde(default)]
+    pub scan_filter: ScanFilter,
+    /// 开发者模式：在设备列表中显示模拟设备
+    #[serde(default)]
+    pub developer_mode: bool,
+    /// 模拟设备参数
+    #[serde(default)]
+    pub simulation: SimulationSettings,
+}
+
+fn default_low_battery_threshold() -> u8 {
+    20
+}
+
+fn default_stale_timeout_secs() -> u64 {
+    10
+}
+
+fn default_reset_energy_on_start() -> bool {
+    true
+}
+
+fn default_summary_thresholds() -> Vec<u16> {
+    vec![100, 140, 160]
+}
+
+impl FloatingWindowSettings {
+    /// 信号中断检测超时，关闭检测时返回 None
+    pub fn stale_timeout(&self) -> Option<Duration> {
+        (self.stale_timeout_secs > 0).then_some(Duration::from_secs(self.stale_timeout_secs))
+    }
 }
 
 impl Default for FloatingWindowSettings {
@@ -19,23 +79,228 @@ impl Default for FloatingWindowSettings {
             auto_start: false,
             show_device_name: true,
             animation_speed: "normal".to_string(),
+            low_battery_threshold: default_low_battery_threshold(),
+            stale_timeout_secs: default_stale_timeout_secs(),
+            summary_thresholds: default_summary_thresholds(),
+            profile: UserProfile::default(),
+            reset_energy_on_start: default_reset_energy_on_start(),
+            hrv: HrvSettings::default(),
+            reconnect: ReconnectPolicy::default(),
+            auto_connect: false,
+            scan_filter: ScanFilter::default(),
+            developer_mode: false,
+            simulation: SimulationSettings::default(),
         }
     }
 }
 
-fn get_settings_path() -> PathBuf {
+/// 心率区间计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneMethod {
+    /// 最大心率的 50% / 60% / 70% / 80% / 90%
+    PercentOfMax,
+    /// Karvonen 储备心率法：静息心率 + (最大心率 - 静息心率) × 百分比
+    Karvonen,
+    /// 使用 custom_zones 中的自定义下限
+    Custom,
+}
+
+/// 五个心率区间的起始百分比
+const ZONE_PERCENTAGES: [f64; 5] = [0.5, 0.6, 0.7, 0.8, 0.9];
+
+/// 用户资料
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserProfile {
+    pub age: Option<u8>,
+    /// 静息心率 (bpm)
+    pub resting_hr: Option<u16>,
+    /// 最大心率 (bpm)，为空时按 220 - 年龄估算
+    pub max_hr: Option<u16>,
+    pub zone_method: ZoneMethod,
+    /// 自定义区间 1-5 的下限 (bpm)，需递增
+    pub custom_zones: Vec<u16>,
+}
+
+impl Default for UserProfile {
+    fn default() -> Self {
+        Self {
+            age: None,
+            resting_hr: None,
+            max_hr: None,
+            zone_method: ZoneMethod::PercentOfMax,
+            custom_zones: Vec::new(),
+        }
+    }
+}
+
+impl UserProfile {
+    /// 最大心率，未设置时按年龄估算
+    pub fn max_heart_rate(&self) -> Option<u16> {
+        self.max_hr
+            .or_else(|| self.age.map(|age| 220u16.saturating_sub(age as u16)))
+            .filter(|max| *max > 0)
+    }
+
+    /// 区间 1-5 的下限 (bpm)，资料不足以计算时返回 None
+    pub fn zone_bounds(&self) -> Option<[u16; 5]> {
+        let bounds = match self.zone_method {
+            ZoneMethod::PercentOfMax => {
+                let max = self.max_heart_rate()? as f64;
+                ZONE_PERCENTAGES.map(|percent| (max * percent).round() as u16)
+            }
+            ZoneMethod::Karvonen => {
+                let max = self.max_heart_rate()? as f64;
+                let resting = self.resting_hr? as f64;
+                if resting >= max {
+                    return None;
+                }
+                ZONE_PERCENTAGES.map(|percent| (resting + (max - resting) * percent).round() as u16)
+            }
+            ZoneMethod::Custom => self.custom_zones.as_slice().try_into().ok()?,
+        };
+
+        bounds.windows(2).all(|pair| pair[0] < pair[1]).then_some(bounds)
+    }
+}
+
+/// 心率变异性计算参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HrvSettings {
+    /// 滑动窗口长度 (秒)
+    pub window_secs: u64,
+    /// "hrv-update" 事件间隔 (秒)，为 0 时不发送
+    pub update_interval_secs: u64,
+}
+
+impl Default for HrvSettings {
+    fn default() -> Self {
+        Self {
+            window_secs: 60,
+            update_interval_secs: 5,
+        }
+    }
+}
+
+/// 模拟设备参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationSettings {
+    /// 静息心率
+    pub rest_bpm: u16,
+    /// 运动模式峰值心率
+    pub exercise_bpm: u16,
+    /// 突增模式峰值心率
+    pub spike_bpm: u16,
+    /// 心率波动幅度 (bpm)，为 0 时输出平滑曲线
+    pub variability: f64,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            rest_bpm: 65,
+            exercise_bpm: 150,
+            spike_bpm: 170,
+            variability: 2.0,
+        }
+    }
+}
+
+/// 设备扫描过滤条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanFilter {
+    /// 需要广播的服务 UUID（支持 "180D" 短格式），为空表示扫描所有设备
+    pub service_uuids: Vec<String>,
+    /// 设备名称正则表达式
+    pub name_pattern: Option<String>,
+    /// 最低信号强度 (dBm)
+    pub min_rssi: Option<i16>,
+    /// 最多返回的设备数量
+    pub max_devices: usize,
+}
+
+impl Default for ScanFilter {
+    fn default() -> Self {
+        Self {
+            service_uuids: vec!["180D".to_string()],
+            name_pattern: None,
+            min_rssi: None,
+            max_devices: 100,
+        }
+    }
+}
+
+/// 断线重连策略（指数退避）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    /// 最大连续重连次数，为空表示不限次数
+    pub max_attempts: Option<u32>,
+    /// 首次重连等待时间 (毫秒)
+    pub initial_delay_ms: u64,
+    /// 每次重连等待时间的增长倍数
+    pub backoff_multiplier: f64,
+    /// 最大等待时间 (毫秒)
+    pub max_delay_ms: u64,
+    /// 随机抖动比例 (0.0 - 1.0)
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_delay_ms: 2000,
+            backoff_multiplier: 2.0,
+            max_delay_ms: 60_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// 是否允许第 attempt 次重连（从 1 开始）
+    pub fn allows_attempt(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+
+    /// 第 attempt 次重连前的等待时间（从 1 开始）
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.initial_delay_ms as f64 * self.backoff_multiplier.max(1.0).powi(exponent);
+        let capped = base.min(self.max_delay_ms.max(self.initial_delay_ms) as f64);
+
+        // 在 [-jitter, +jitter] 范围内随机浮动，无效的抖动比例视为不抖动
+        let jitter = if self.jitter.is_nan() { 0.0 } else { self.jitter.clamp(0.0, 1.0) };
+        let random = heart::random_u64() as f64 / u64::MAX as f64;
+        let factor = 1.0 + jitter * (random * 2.0 - 1.0);
+
+        Duration::from_millis((capped * factor).max(0.0) as u64)
+    }
+}
+
+/// 应用配置目录，系统没有配置目录时返回 `SettingsIo`
+pub(crate) fn get_config_dir() -> AppResult<PathBuf> {
     let config_dir = dirs::config_dir()
-        .expect("Failed to get config directory")
+        .ok_or_else(|| AppError::new(ErrorCode::SettingsIo, "Failed to get config directory"))?
         .join("heart");
     
     // 确保目录存在
     let _ = fs::create_dir_all(&config_dir);
     
-    config_dir.join("settings.json")
+    Ok(config_dir)
+}
+
+fn get_settings_path() -> AppResult<PathBuf> {
+    Ok(get_config_dir()?.join("settings.json"))
 }
 
-pub fn load_settings() -> Result<FloatingWindowSettings, String> {
-    let path = get_settings_path();
+pub fn load_settings() -> AppResult<FloatingWindowSettings> {
+    let path = get_settings_path()?;
     
     if path.exists() {
         match fs::read_to_string(&path) {
@@ -58,8 +323,8 @@ pub fn load_settings() -> Result<FloatingWindowSettings, String> {
     }
 }
 
-pub fn save_settings(settings: &FloatingWindowSettings) -> Result<(), String> {
-    let path = get_settings_path();
+pub fn save_settings(settings: &FloatingWindowSettings) -> AppResult<()> {
+    let path = get_settings_path()?;
     
     match serde_json::to_string_pretty(&settings) {
         Ok(content) => {
@@ -68,35 +333,160 @@ pub fn save_settings(settings: &FloatingWindowSettings) -> Result<(), String> {
                     eprintln!("Settings saved to: {:?}", path);
                     Ok(())
                 }
-                Err(e) => Err(format!("Failed to write settings file: {}", e)),
+                Err(e) => Err(AppError::new(
+                    ErrorCode::SettingsIo,
+                    format!("Failed to write settings file: {}", e),
+                )),
             }
         }
-        Err(e) => Err(format!("Failed to serialize settings: {}", e)),
+        Err(e) => Err(AppError::new(
+            ErrorCode::SettingsIo,
+            format!("Failed to serialize settings: {}", e),
+        )),
     }
 }
 
-pub fn update_settings(updates: FloatingWindowSettings) -> Result<FloatingWindowSettings, String> {
+pub fn update_settings(updates: FloatingWindowSettings) -> AppResult<FloatingWindowSettings> {
     save_settings(&updates)?;
     Ok(updates)
 }
 
-pub fn reset_settings() -> Result<FloatingWindowSettings, String> {
+pub fn reset_settings() -> AppResult<FloatingWindowSettings> {
     let default = FloatingWindowSettings::default();
     save_settings(&default)?;
     Ok(default)
 }
 
 #[tauri::command]
-pub async fn get_settings() -> Result<FloatingWindowSettings, String> {
+pub async fn get_settings() -> AppResult<FloatingWindowSettings> {
     load_settings()
 }
 
 #[tauri::command]
-pub async fn set_settings(settings: FloatingWindowSettings) -> Result<FloatingWindowSettings, String> {
+pub async fn set_settings(settings: FloatingWindowSettings) -> AppResult<FloatingWindowSettings> {
     update_settings(settings)
 }
 
 #[tauri::command]
-pub async fn reset_to_default() -> Result<FloatingWindowSettings, String> {
+pub async fn reset_to_default() -> AppResult<FloatingWindowSettings> {
     reset_settings()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(zone_method: ZoneMethod) -> UserProfile {
+        UserProfile {
+            zone_method,
+            ..UserProfile::default()
+        }
+    }
+
+    fn policy(backoff_multiplier: f64, jitter: f64) -> ReconnectPolicy {
+        ReconnectPolicy {
+            backoff_multiplier,
+            jitter,
+            ..ReconnectPolicy::default()
+        }
+    }
+
+    fn delays_ms(policy: &ReconnectPolicy, attempts: std::ops::RangeInclusive<u32>) -> Vec<u128> {
+        attempts.map(|attempt| policy.delay_for_attempt(attempt).as_millis()).collect()
+    }
+
+    #[test]
+    fn reconnect_delay_grows_exponentially_up_to_cap() {
+        let policy = policy(2.0, 0.0);
+        assert_eq!(delays_ms(&policy, 1..=7), [2_000, 4_000, 8_000, 16_000, 32_000, 60_000, 60_000]);
+        assert_eq!(policy.delay_for_attempt(u32::MAX).as_millis(), 60_000);
+
+        // 最大等待时间小于首次等待时间时以首次等待时间为准
+        let policy = ReconnectPolicy {
+            max_delay_ms: 500,
+            ..policy
+        };
+        assert_eq!(delays_ms(&policy, 1..=3), [2_000, 2_000, 2_000]);
+    }
+
+    #[test]
+    fn reconnect_delay_jitter_stays_in_range() {
+        let policy = policy(2.0, 0.2);
+        for _ in 0..200 {
+            let delay = policy.delay_for_attempt(2).as_millis();
+            assert!((3_200..=4_800).contains(&delay), "{delay}");
+        }
+    }
+
+    #[test]
+    fn reconnect_delay_ignores_bad_inputs() {
+        // 小于 1 或无效的倍数不缩短等待时间
+        for multiplier in [0.5, -2.0, f64::NAN] {
+            assert_eq!(delays_ms(&policy(multiplier, 0.0), 1..=3), [2_000, 2_000, 2_000]);
+        }
+        // 无效的抖动比例视为不抖动，超出范围的按边界处理
+        assert_eq!(delays_ms(&policy(2.0, f64::NAN), 1..=2), [2_000, 4_000]);
+        assert_eq!(delays_ms(&policy(2.0, -1.0), 1..=2), [2_000, 4_000]);
+        for _ in 0..200 {
+            assert!(policy(2.0, 5.0).delay_for_attempt(1).as_millis() <= 4_000);
+        }
+    }
+
+    #[test]
+    fn reconnect_attempt_limit() {
+        let unlimited = ReconnectPolicy::default();
+        assert!(unlimited.allows_attempt(1));
+        assert!(unlimited.allows_attempt(u32::MAX));
+
+        let limited = ReconnectPolicy {
+            max_attempts: Some(3),
+            ..ReconnectPolicy::default()
+        };
+        assert!(limited.allows_attempt(3));
+        assert!(!limited.allows_attempt(4));
+    }
+
+    #[test]
+    fn percent_of_max_zones() {
+        let mut profile = profile(ZoneMethod::PercentOfMax);
+        assert_eq!(profile.zone_bounds(), None);
+
+        // 未设置最大心率时按 220 - 年龄估算
+        profile.age = Some(40);
+        assert_eq!(profile.zone_bounds(), Some([90, 108, 126, 144, 162]));
+
+        profile.max_hr = Some(200);
+        assert_eq!(profile.zone_bounds(), Some([100, 120, 140, 160, 180]));
+    }
+
+    #[test]
+    fn karvonen_zones() {
+        let mut profile = profile(ZoneMethod::Karvonen);
+        profile.max_hr = Some(180);
+        assert_eq!(profile.zone_bounds(), None);
+
+        profile.resting_hr = Some(60);
+        assert_eq!(profile.zone_bounds(), Some([120, 132, 144, 156, 168]));
+
+        profile.resting_hr = Some(180);
+        assert_eq!(profile.zone_bounds(), None);
+        profile.resting_hr = Some(200);
+        assert_eq!(profile.zone_bounds(), None);
+    }
+
+    #[test]
+    fn custom_zones_must_increase() {
+        let mut profile = profile(ZoneMethod::Custom);
+        assert_eq!(profile.zone_bounds(), None);
+
+        profile.custom_zones = vec![95, 115, 135, 155, 175];
+        assert_eq!(profile.zone_bounds(), Some([95, 115, 135, 155, 175]));
+
+        profile.custom_zones = vec![95, 115, 115, 155, 175];
+        assert_eq!(profile.zone_bounds(), None);
+        profile.custom_zones = vec![175, 155, 135, 115, 95];
+        assert_eq!(profile.zone_bounds(), None);
+        profile.custom_zones = vec![95, 115, 135, 155];
+        assert_eq!(profile.zone_bounds(), None);
+    }
+}
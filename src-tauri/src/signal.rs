@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::heart;
+
+/// 信号质量采样间隔
+pub(crate) const SIGNAL_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 信号质量报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SignalQuality {
+    pub device_id: String,
+    /// 信号强度 (dBm)
+    pub rssi: Option<i16>,
+    /// 统计窗口内收到的通知数
+    pub notification_count: u32,
+    /// 通知频率 (次/秒)
+    pub notification_rate: f64,
+    /// 相邻通知的平均间隔 (毫秒)
+    pub mean_gap_ms: Option<f64>,
+    /// 相邻通知的最大间隔 (毫秒)
+    pub max_gap_ms: Option<f64>,
+    pub timestamp: u64,
+}
+
+/// 信号中断原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StaleReason {
+    /// 设备仍在连接或仍在广播，但已停止发送心率（如手环关闭了心率广播）
+    BroadcastEnded,
+    /// 连接已断开或设备已不在范围内
+    LinkLost,
+}
+
+/// 信号中断事件
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleSignal {
+    pub device_id: String,
+    pub reason: StaleReason,
+    /// 最后一次收到心率的时间戳，从未收到时为空
+    pub last_update: Option<u64>,
+    /// 已经多久未收到心率 (毫秒)
+    pub silent_ms: u64,
+    pub timestamp: u64,
+}
+
+/// 广播 "heart-rate-stale" 事件
+pub(crate) fn emit_stale(
+    app: &AppHandle,
+    device_id: &str,
+    reason: StaleReason,
+    last_update: Option<u64>,
+    silent_for: Duration,
+) {
+    eprintln!("Heart rate signal stale ({device_id}): {reason:?}, silent for {silent_for:?}");
+    let _ = app.emit(
+        "heart-rate-stale",
+        StaleSignal {
+            device_id: device_id.to_string(),
+            reason,
+            last_update,
+            silent_ms: silent_for.as_millis() as u64,
+            timestamp: heart::current_timestamp_millis(),
+        },
+    );
+}
+
+/// 通知间隔统计，每次报告后重新开始一个窗口
+pub(crate) struct NotificationStats {
+    window_start: Instant,
+    last_notification: Option<Instant>,
+    count: u32,
+    gap_count: u32,
+    gap_sum: Duration,
+    max_gap: Duration,
+}
+
+impl NotificationStats {
+    pub(crate) fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            last_notification: None,
+            count: 0,
+            gap_count: 0,
+            gap_sum: Duration::ZERO,
+            max_gap: Duration::ZERO,
+        }
+    }
+
+    /// 记录一次通知
+    pub(crate) fn record(&mut self) {
+        let now = Instant::now();
+        if let Some(last) = self.last_notification {
+            let gap = now.duration_since(last);
+            self.gap_count += 1;
+            self.gap_sum += gap;
+            self.max_gap = self.max_gap.max(gap);
+        }
+        self.last_notification = Some(now);
+        self.count += 1;
+    }
+
+    /// 生成当前窗口的报告并开始新窗口
+    pub(crate) fn report(&mut self, device_id: &str, rssi: Option<i16>) -> SignalQuality {
+        let elapsed = self.window_start.elapsed().as_secs_f64();
+        let (mean_gap_ms, max_gap_ms) = if self.gap_count > 0 {
+            (
+                Some(self.gap_sum.as_secs_f64() * 1000.0 / self.gap_count as f64),
+                Some(self.max_gap.as_secs_f64() * 1000.0),
+            )
+        } else {
+            (None, None)
+        };
+
+        let report = SignalQuality {
+            device_id: device_id.to_string(),
+            rssi,
+            notification_count: self.count,
+            notification_rate: if elapsed > 0.0 { self.count as f64 / elapsed } else { 0.0 },
+            mean_gap_ms,
+            max_gap_ms,
+            timestamp: heart::current_timestamp_millis(),
+        };
+
+        // 保留最后一次通知时间，跨窗口的间隔同样计入统计
+        *self = Self {
+            last_notification: self.last_notification,
+            ..Self::new()
+        };
+
+        report
+    }
+}
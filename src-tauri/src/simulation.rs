@@ -0,0 +1,295 @@
+use std::error::Error;
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::connection::{ConnectionState, ConnectionTracker};
+use crate::heart::{self, DeviceInfo, HeartRateMeasurement};
+use crate::settings::SimulationSettings;
+use crate::source::{HeartRateSource, SourceFuture};
+
+/// 模拟设备 ID 前缀
+const SIMULATED_DEVICE_PREFIX: &str = "simulated:";
+/// 模拟通知间隔，与大多数手环一致
+const NOTIFICATION_INTERVAL: Duration = Duration::from_secs(1);
+/// 运动模式一个周期：热身、保持、放松
+const EXERCISE_CYCLE_SECS: f64 = 600.0;
+/// 突增模式两次突增的间隔与持续时间
+const SPIKE_INTERVAL_SECS: f64 = 60.0;
+const SPIKE_DURATION_SECS: f64 = 10.0;
+/// 心率波动幅度上限 (bpm)，RR 间期的随机浮动为幅度的 2%，超过 50 时 RR 间期会变为负数
+const MAX_VARIABILITY: f64 = 20.0;
+
+/// 模拟心率模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationProfile {
+    Rest,
+    Exercise,
+    Spike,
+}
+
+impl SimulationProfile {
+    const ALL: [Self; 3] = [Self::Rest, Self::Exercise, Self::Spike];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::Rest => "rest",
+            Self::Exercise => "exercise",
+            Self::Spike => "spike",
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            Self::Rest => "Simulated device (rest)",
+            Self::Exercise => "Simulated device (exercise)",
+            Self::Spike => "Simulated device (spike)",
+        }
+    }
+
+    /// 从设备 ID 解析模拟模式，非模拟设备返回 None
+    pub fn from_device_id(device_id: &str) -> Option<Self> {
+        let profile = device_id.strip_prefix(SIMULATED_DEVICE_PREFIX)?;
+        Self::ALL.into_iter().find(|p| p.id() == profile)
+    }
+
+    fn device_id(self) -> String {
+        format!("{SIMULATED_DEVICE_PREFIX}{}", self.id())
+    }
+}
+
+/// 模拟设备列表（开发者模式下显示在设备列表中）
+pub fn simulated_devices() -> Vec<DeviceInfo> {
+    SimulationProfile::ALL
+        .into_iter()
+        .map(|profile| DeviceInfo {
+            id: profile.device_id(),
+            mac_address: None,
+            name: Some(profile.display_name().to_string()),
+            connected: false,
+            rssi: None,
+        })
+        .collect()
+}
+
+/// 简单的 xorshift 随机数生成器，仅用于生成模拟数据
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> Self {
+        Self(heart::random_u64() | 1)
+    }
+
+    /// [0, 1) 均匀分布
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// [-1, 1) 均匀分布
+    fn next_signed(&mut self) -> f64 {
+        self.next_f64() * 2.0 - 1.0
+    }
+}
+
+/// 模拟心率数据源
+pub(crate) struct SimulatedSource {
+    profile: SimulationProfile,
+    settings: SimulationSettings,
+}
+
+impl SimulatedSource {
+    pub(crate) fn new(profile: SimulationProfile, settings: SimulationSettings) -> Self {
+        Self { profile, settings }
+    }
+
+    /// 第 elapsed 秒的目标心率
+    fn target_bpm(&self, elapsed: f64) -> f64 {
+        let rest = self.settings.rest_bpm as f64;
+        match self.profile {
+            SimulationProfile::Rest => rest,
+            SimulationProfile::Exercise => {
+                // 20% 时间热身，50% 保持，30% 放松
+                let peak = self.settings.exercise_bpm as f64;
+                let phase = (elapsed % EXERCISE_CYCLE_SECS) / EXERCISE_CYCLE_SECS;
+                let level = if phase < 0.2 {
+                    phase / 0.2
+                } else if phase < 0.7 {
+                    1.0
+                } else {
+                    1.0 - (phase - 0.7) / 0.3
+                };
+                rest + (peak - rest) * level
+            }
+            SimulationProfile::Spike => {
+                let in_spike = elapsed % SPIKE_INTERVAL_SECS < SPIKE_DURATION_SECS;
+                if in_spike {
+                    self.settings.spike_bpm as f64
+                } else {
+                    rest
+                }
+            }
+        }
+    }
+
+    /// 心率波动幅度，限制在 [0, MAX_VARIABILITY]
+    fn variability(&self) -> f64 {
+        if self.settings.variability.is_nan() {
+            return 0.0;
+        }
+        self.settings.variability.clamp(0.0, MAX_VARIABILITY)
+    }
+
+    /// 每秒生成一次模拟通知，直到任务被取消
+    async fn simulate(
+        self,
+        app: AppHandle,
+        tracker: ConnectionTracker,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        tracker.set(ConnectionState::Connecting).await;
+        tracker.set(ConnectionState::DiscoveringServices).await;
+        tracker.set(ConnectionState::Subscribed).await;
+
+        let device_id = self.profile.device_id();
+        let variability = self.variability();
+        let mut rng = XorShift::new();
+        let mut interval = tokio::time::interval(NOTIFICATION_INTERVAL);
+        let mut bpm = self.settings.rest_bpm as f64;
+        let mut energy: f64 = 0.0;
+        let mut elapsed = 0.0;
+        // 上一次通知后尚未结束的心跳时间 (毫秒)
+        let mut pending_ms = 0.0;
+
+        loop {
+            interval.tick().await;
+
+            // 向目标心率平滑过渡，叠加呼吸性窦性心律不齐与随机噪声
+            let target = self.target_bpm(elapsed);
+            bpm += (target - bpm) * 0.15;
+            let breathing = (elapsed * TAU / 4.0).sin() * 2.0 * variability;
+            let current = (bpm + breathing + rng.next_signed() * variability).clamp(30.0, 220.0);
+
+            // 在这一秒内完成的心跳生成 RR 间期
+            let mut rr_intervals = Vec::new();
+            pending_ms += 1000.0;
+            loop {
+                let rr = next_rr(&mut rng, current, variability);
+                if rr > pending_ms {
+                    break;
+                }
+                pending_ms -= rr;
+                // 与真实设备一致，按 1/1024 秒精度上报
+                rr_intervals.push((rr * 1024.0 / 1000.0).round() * 1000.0 / 1024.0);
+            }
+
+            // 粗略估算能量消耗 (kJ)
+            energy += current / 60.0 * 0.4;
+
+            let measurement = HeartRateMeasurement {
+                bpm: current.round() as u16,
+                sensor_contact_supported: true,
+                sensor_contact_detected: true,
+                energy_expended: Some(energy.min(u16::MAX as f64) as u16),
+                rr_intervals,
+                timestamp: heart::current_timestamp_millis(),
+            };
+            heart::emit_heart_rate_sample(&app, &device_id, measurement).await;
+
+            elapsed += NOTIFICATION_INTERVAL.as_secs_f64();
+        }
+    }
+}
+
+/// 按当前心率生成一个 RR 间期 (毫秒)，随机浮动为波动幅度的 2%
+fn next_rr(rng: &mut XorShift, bpm: f64, variability: f64) -> f64 {
+    60_000.0 / bpm * (1.0 + rng.next_signed() * 0.02 * variability)
+}
+
+impl HeartRateSource for SimulatedSource {
+    fn run(self: Box<Self>, app: AppHandle, tracker: ConnectionTracker) -> SourceFuture {
+        let source = *self;
+        Box::pin(source.simulate(app, tracker))
+    }
+
+    fn uses_adapter(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(profile: SimulationProfile, variability: f64) -> SimulatedSource {
+        SimulatedSource::new(
+            profile,
+            SimulationSettings {
+                variability,
+                ..SimulationSettings::default()
+            },
+        )
+    }
+
+    fn assert_bpm(source: &SimulatedSource, elapsed: f64, expected: f64) {
+        let actual = source.target_bpm(elapsed);
+        assert!((actual - expected).abs() < 1e-9, "{elapsed}s: {actual} != {expected}");
+    }
+
+    #[test]
+    fn rest_target_is_constant() {
+        let rest = source(SimulationProfile::Rest, 2.0);
+        for elapsed in [0.0, 30.0, 600.0, 3600.0] {
+            assert_bpm(&rest, elapsed, 65.0);
+        }
+    }
+
+    #[test]
+    fn exercise_target_follows_cycle() {
+        let exercise = source(SimulationProfile::Exercise, 2.0);
+        // 热身 0-120s，保持 120-420s，放松 420-600s
+        assert_bpm(&exercise, 0.0, 65.0);
+        assert_bpm(&exercise, 60.0, 107.5);
+        assert_bpm(&exercise, 120.0, 150.0);
+        assert_bpm(&exercise, 300.0, 150.0);
+        assert_bpm(&exercise, 510.0, 107.5);
+        assert_bpm(&exercise, 600.0, 65.0);
+        assert_bpm(&exercise, 660.0, 107.5);
+    }
+
+    #[test]
+    fn spike_target_repeats_every_minute() {
+        let spike = source(SimulationProfile::Spike, 2.0);
+        assert_bpm(&spike, 0.0, 170.0);
+        assert_bpm(&spike, 9.5, 170.0);
+        assert_bpm(&spike, 10.0, 65.0);
+        assert_bpm(&spike, 59.0, 65.0);
+        assert_bpm(&spike, 60.0, 170.0);
+        assert_bpm(&spike, 70.0, 65.0);
+    }
+
+    #[test]
+    fn clamps_variability() {
+        assert_eq!(source(SimulationProfile::Rest, -1.0).variability(), 0.0);
+        assert_eq!(source(SimulationProfile::Rest, f64::NAN).variability(), 0.0);
+        assert_eq!(source(SimulationProfile::Rest, 5.0).variability(), 5.0);
+        assert_eq!(source(SimulationProfile::Rest, 1000.0).variability(), MAX_VARIABILITY);
+    }
+
+    #[test]
+    fn rr_intervals_stay_positive() {
+        let mut rng = XorShift::new();
+        let variability = source(SimulationProfile::Rest, f64::INFINITY).variability();
+
+        for bpm in [30.0, 65.0, 150.0, 220.0] {
+            let nominal = 60_000.0 / bpm;
+            for _ in 0..1000 {
+                let rr = next_rr(&mut rng, bpm, variability);
+                assert!(rr > 0.0);
+                assert!((nominal * 0.6..=nominal * 1.4).contains(&rr), "{bpm} bpm: {rr}");
+            }
+        }
+    }
+}
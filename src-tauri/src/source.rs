@@ -0,0 +1,22 @@
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+
+use tauri::AppHandle;
+
+use crate::connection::ConnectionTracker;
+
+/// 数据源运行结果
+pub(crate) type SourceFuture = Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>>;
+
+/// 心率数据源
+///
+/// 每个心率流会话持有一个数据源，`run` 在独立任务中运行，
+/// 通过 `heart::emit_heart_rate_sample` 广播样本、通过 `tracker` 上报连接状态，
+/// 直到出错结束或任务被取消
+pub(crate) trait HeartRateSource: Send {
+    fn run(self: Box<Self>, app: AppHandle, tracker: ConnectionTracker) -> SourceFuture;
+
+    /// 是否通过蓝牙适配器获取数据，适配器不可用时只暂停这类会话
+    fn uses_adapter(&self) -> bool;
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::heart::{self, HeartRateSample};
+use crate::hrv::HrvMetrics;
+use crate::settings;
+use crate::zone::ZoneDuration;
+
+/// 心率达到阈值的累计时长
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdDuration {
+    pub bpm: u16,
+    pub duration_ms: u64,
+}
+
+/// 心率流会话统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    /// 开始与结束时间 (Unix 毫秒)
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_ms: u64,
+    pub sample_count: u32,
+    pub avg_bpm: Option<f64>,
+    pub min_bpm: Option<u16>,
+    pub max_bpm: Option<u16>,
+    /// 各阈值（设置中的 summary_thresholds）以上的时长
+    pub time_above: Vec<ThresholdDuration>,
+    /// 各心率区间内的时长，用户资料不足以计算区间时为空
+    pub time_in_zones: Vec<ZoneDuration>,
+    /// 支持接触检测但未检测到皮肤接触的样本数
+    pub no_contact_samples: u32,
+    /// 断线重连次数
+    pub reconnects: u32,
+    /// 整个会话的心率变异性指标，RR 间期不足时为空
+    pub hrv: Option<HrvMetrics>,
+}
+
+/// 会话统计累加器，每个样本到达时更新
+struct SummaryAccumulator {
+    started_at: u64,
+    thresholds: Vec<u16>,
+    time_above: Vec<u64>,
+    sample_count: u32,
+    bpm_sum: u64,
+    min_bpm: Option<u16>,
+    max_bpm: Option<u16>,
+    /// 上一个样本的时间戳与心率
+    last_sample: Option<(u64, u16)>,
+    no_contact_samples: u32,
+    reconnects: u32,
+}
+
+impl SummaryAccumulator {
+    fn new(thresholds: Vec<u16>, started_at: u64) -> Self {
+        Self {
+            started_at,
+            time_above: vec![0; thresholds.len()],
+            thresholds,
+            sample_count: 0,
+            bpm_sum: 0,
+            min_bpm: None,
+            max_bpm: None,
+            last_sample: None,
+            no_contact_samples: 0,
+            reconnects: 0,
+        }
+    }
+
+    /// 上一个样本的心率持续到 `until`，计入对应的阈值时长
+    fn accumulate_time(&mut self, until: u64) {
+        let Some((timestamp, bpm)) = self.last_sample else {
+            return;
+        };
+        let gap = heart::sample_duration(timestamp, until);
+        for (threshold, total) in self.thresholds.iter().zip(self.time_above.iter_mut()) {
+            if bpm >= *threshold {
+                *total += gap;
+            }
+        }
+    }
+
+    fn record(&mut self, sample: &HeartRateSample) {
+        let measurement = &sample.measurement;
+        self.accumulate_time(measurement.timestamp);
+
+        self.sample_count += 1;
+        self.bpm_sum += measurement.bpm as u64;
+        self.min_bpm = Some(self.min_bpm.map_or(measurement.bpm, |min| min.min(measurement.bpm)));
+        self.max_bpm = Some(self.max_bpm.map_or(measurement.bpm, |max| max.max(measurement.bpm)));
+        if measurement.sensor_contact_supported && !measurement.sensor_contact_detected {
+            self.no_contact_samples += 1;
+        }
+        self.last_sample = Some((measurement.timestamp, measurement.bpm));
+    }
+
+    fn finish(mut self, ended_at: u64) -> SessionSummary {
+        self.accumulate_time(ended_at);
+
+        SessionSummary {
+            started_at: self.started_at,
+            ended_at,
+            duration_ms: ended_at.saturating_sub(self.started_at),
+            sample_count: self.sample_count,
+            avg_bpm: (self.sample_count > 0).then(|| self.bpm_sum as f64 / self.sample_count as f64),
+            min_bpm: self.min_bpm,
+            max_bpm: self.max_bpm,
+            time_above: self
+                .thresholds
+                .into_iter()
+                .zip(self.time_above)
+                .map(|(bpm, duration_ms)| ThresholdDuration { bpm, duration_ms })
+                .collect(),
+            time_in_zones: Vec::new(),
+            no_contact_samples: self.no_contact_samples,
+            reconnects: self.reconnects,
+            hrv: None,
+        }
+    }
+}
+
+/// 按设备 ID 索引的进行中会话统计
+static SUMMARIES: Mutex<BTreeMap<String, SummaryAccumulator>> = Mutex::const_new(BTreeMap::new());
+
+/// 开始统计设备的新会话
+pub(crate) async fn begin(device_id: &str) {
+    let thresholds = settings::load_settings()
+        .map(|s| s.summary_thresholds)
+        .unwrap_or_default();
+    SUMMARIES
+        .lock()
+        .await
+        .insert(
+            device_id.to_string(),
+            SummaryAccumulator::new(thresholds, heart::current_timestamp_millis()),
+        );
+}
+
+/// 计入一个样本（设备没有进行中的会话时忽略）
+pub(crate) async fn record_sample(sample: &HeartRateSample) {
+    if let Some(accumulator) = SUMMARIES.lock().await.get_mut(&sample.device_id) {
+        accumulator.record(sample);
+    }
+}
+
+/// 计入一次断线重连
+pub(crate) async fn record_reconnect(device_id: &str) {
+    if let Some(accumulator) = SUMMARIES.lock().await.get_mut(device_id) {
+        accumulator.reconnects += 1;
+    }
+}
+
+/// 结束统计并返回会话摘要，附带会话的区间时长与 HRV 指标
+pub(crate) async fn finish(
+    device_id: &str,
+    time_in_zones: Vec<ZoneDuration>,
+    hrv: Option<HrvMetrics>,
+) -> Option<SessionSummary> {
+    let accumulator = SUMMARIES.lock().await.remove(device_id)?;
+    Some(SessionSummary {
+        time_in_zones,
+        hrv,
+        ..accumulator.finish(heart::current_timestamp_millis())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heart::HeartRateMeasurement;
+
+    fn sample(timestamp: u64, bpm: u16, contact_supported: bool, contact_detected: bool) -> HeartRateSample {
+        HeartRateSample {
+            device_id: "band".to_string(),
+            label: None,
+            zone: None,
+            measurement: HeartRateMeasurement {
+                bpm,
+                sensor_contact_supported: contact_supported,
+                sensor_contact_detected: contact_detected,
+                energy_expended: None,
+                rr_intervals: Vec::new(),
+                timestamp,
+            },
+        }
+    }
+
+    #[test]
+    fn summarizes_session() {
+        let mut accumulator = SummaryAccumulator::new(vec![100, 150], 0);
+        accumulator.record(&sample(0, 120, true, true));
+        accumulator.record(&sample(1_000, 160, true, false));
+        accumulator.record(&sample(3_000, 90, false, false));
+        // 155 bpm 之后 20s 没有样本，只计入 5s
+        accumulator.record(&sample(20_000, 155, true, false));
+        accumulator.record(&sample(40_000, 110, false, true));
+        let summary = accumulator.finish(41_000);
+
+        assert_eq!(summary.duration_ms, 41_000);
+        assert_eq!(summary.sample_count, 5);
+        assert_eq!(summary.avg_bpm, Some(127.0));
+        assert_eq!((summary.min_bpm, summary.max_bpm), (Some(90), Some(160)));
+
+        let time_above: Vec<(u16, u64)> = summary.time_above.iter().map(|t| (t.bpm, t.duration_ms)).collect();
+        assert_eq!(time_above, [(100, 9_000), (150, 7_000)]);
+
+        // 只统计支持接触检测的样本
+        assert_eq!(summary.no_contact_samples, 2);
+    }
+
+    #[test]
+    fn empty_session_has_no_statistics() {
+        let summary = SummaryAccumulator::new(vec![100], 1_000).finish(61_000);
+
+        assert_eq!(summary.duration_ms, 60_000);
+        assert_eq!(summary.sample_count, 0);
+        assert_eq!((summary.avg_bpm, summary.min_bpm, summary.max_bpm), (None, None, None));
+        assert_eq!(summary.time_above[0].duration_ms, 0);
+    }
+
+    #[tokio::test]
+    async fn counts_reconnects() {
+        SUMMARIES
+            .lock()
+            .await
+            .insert("reconnect-test".to_string(), SummaryAccumulator::new(Vec::new(), 0));
+
+        record_reconnect("reconnect-test").await;
+        record_reconnect("reconnect-test").await;
+        record_reconnect("other-device").await;
+
+        let summary = finish("reconnect-test", Vec::new(), None).await.unwrap();
+        assert_eq!(summary.reconnects, 2);
+        assert!(finish("reconnect-test", Vec::new(), None).await.is_none());
+    }
+}
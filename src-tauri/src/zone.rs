@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::error::AppResult;
+use crate::heart;
+use crate::settings;
+
+/// 心率区间切换事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneChanged {
+    pub device_id: String,
+    /// 新区间 (0 表示低于区间 1)
+    pub zone: u8,
+    /// 切换前的区间，会话的第一个样本为空
+    pub previous: Option<u8>,
+    pub bpm: u16,
+    pub timestamp: u64,
+}
+
+/// 当前心率区间配置
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartRateZones {
+    pub method: settings::ZoneMethod,
+    /// 区间 1-5 的下限 (bpm)
+    pub bounds: [u16; 5],
+}
+
+/// 会话在某个区间内的累计时长
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneDuration {
+    /// 区间 (0 表示低于区间 1)
+    pub zone: u8,
+    pub duration_ms: u64,
+}
+
+/// 单个心率流的区间跟踪
+struct ZoneTracker {
+    bounds: [u16; 5],
+    current: Option<u8>,
+    /// 上一个样本的时间戳
+    last_sample_at: Option<u64>,
+    /// 区间 0-5 的累计时长 (毫秒)
+    time_in_zones: [u64; 6],
+}
+
+impl ZoneTracker {
+    fn new(bounds: [u16; 5]) -> Self {
+        Self {
+            bounds,
+            current: None,
+            last_sample_at: None,
+            time_in_zones: [0; 6],
+        }
+    }
+
+    /// 上一个样本所在区间持续到 `until`，计入该区间的时长
+    fn accumulate_time(&mut self, until: u64) {
+        let (Some(timestamp), Some(zone)) = (self.last_sample_at, self.current) else {
+            return;
+        };
+        self.time_in_zones[zone as usize] += heart::sample_duration(timestamp, until);
+    }
+
+    /// 计入一个样本，返回所在区间与切换前的区间
+    fn record(&mut self, bpm: u16, timestamp: u64) -> (u8, Option<u8>) {
+        self.accumulate_time(timestamp);
+        self.last_sample_at = Some(timestamp);
+
+        let zone = zone_index(&self.bounds, bpm);
+        (zone, self.current.replace(zone))
+    }
+
+    fn finish(mut self, ended_at: u64) -> Vec<ZoneDuration> {
+        self.accumulate_time(ended_at);
+        (0u8..)
+            .zip(self.time_in_zones)
+            .map(|(zone, duration_ms)| ZoneDuration { zone, duration_ms })
+            .collect()
+    }
+}
+
+/// 按设备 ID 索引的区间跟踪，会话开始时按用户资料计算区间
+static ZONE_TRACKERS: Mutex<BTreeMap<String, ZoneTracker>> = Mutex::const_new(BTreeMap::new());
+
+/// 心率所在区间：0 表示低于区间 1，1-5 为对应区间
+fn zone_index(bounds: &[u16; 5], bpm: u16) -> u8 {
+    bounds.iter().filter(|bound| bpm >= **bound).count() as u8
+}
+
+/// 开始跟踪设备的心率区间，用户资料不足时不标注区间
+pub(crate) async fn begin(device_id: &str) {
+    let bounds = settings::load_settings()
+        .ok()
+        .and_then(|s| s.profile.zone_bounds());
+
+    let mut trackers = ZONE_TRACKERS.lock().await;
+    match bounds {
+        Some(bounds) => {
+            trackers.insert(device_id.to_string(), ZoneTracker::new(bounds));
+        }
+        None => {
+            trackers.remove(device_id);
+        }
+    }
+}
+
+/// 计算样本所在区间，区间变化时发送 "zone-changed" 事件
+pub(crate) async fn classify(app: &AppHandle, device_id: &str, bpm: u16, timestamp: u64) -> Option<u8> {
+    let mut trackers = ZONE_TRACKERS.lock().await;
+    let tracker = trackers.get_mut(device_id)?;
+
+    let (zone, previous) = tracker.record(bpm, timestamp);
+    if previous != Some(zone) {
+        let _ = app.emit(
+            "zone-changed",
+            ZoneChanged {
+                device_id: device_id.to_string(),
+                zone,
+                previous,
+                bpm,
+                timestamp,
+            },
+        );
+    }
+
+    Some(zone)
+}
+
+/// 停止跟踪设备的心率区间，返回各区间的累计时长（未标注区间时为空）
+pub(crate) async fn end(device_id: &str) -> Vec<ZoneDuration> {
+    ZONE_TRACKERS
+        .lock()
+        .await
+        .remove(device_id)
+        .map(|tracker| tracker.finish(heart::current_timestamp_millis()))
+        .unwrap_or_default()
+}
+
+/// 按当前用户资料计算心率区间，资料不足时返回空
+#[tauri::command]
+pub async fn get_heart_rate_zones() -> AppResult<Option<HeartRateZones>> {
+    let profile = settings::load_settings()?.profile;
+    Ok(profile.zone_bounds().map(|bounds| HeartRateZones {
+        method: profile.zone_method,
+        bounds,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: [u16; 5] = [100, 120, 140, 160, 180];
+
+    #[test]
+    fn classifies_zones_by_lower_bound() {
+        let cases = [(0, 0), (99, 0), (100, 1), (139, 2), (140, 3), (179, 4), (180, 5), (250, 5)];
+        for (bpm, zone) in cases {
+            assert_eq!(zone_index(&BOUNDS, bpm), zone, "{bpm} bpm");
+        }
+    }
+
+    #[test]
+    fn accumulates_time_in_zones() {
+        let mut tracker = ZoneTracker::new(BOUNDS);
+
+        assert_eq!(tracker.record(110, 0), (1, None));
+        assert_eq!(tracker.record(130, 1_000), (2, Some(1)));
+        assert_eq!(tracker.record(130, 2_000), (2, Some(2)));
+        // 断线 18s 后恢复，只计入 5s
+        assert_eq!(tracker.record(170, 20_000), (4, Some(2)));
+
+        let durations: Vec<(u8, u64)> = tracker
+            .finish(21_000)
+            .into_iter()
+            .map(|d| (d.zone, d.duration_ms))
+            .collect();
+        assert_eq!(durations, [(0, 0), (1, 1_000), (2, 6_000), (3, 0), (4, 1_000), (5, 0)]);
+    }
+
+    #[test]
+    fn empty_session_has_no_time_in_zones() {
+        let durations = ZoneTracker::new(BOUNDS).finish(10_000);
+        assert!(durations.iter().all(|d| d.duration_ms == 0));
+    }
+}
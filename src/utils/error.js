@@ -0,0 +1,30 @@
+// 后端错误码对应的本地化提示
+const ERROR_MESSAGES = {
+    AdapterMissing: { title: "未找到蓝牙适配器", hint: "请插入蓝牙适配器并确认驱动已加载" },
+    AdapterOff: { title: "蓝牙未开启", hint: "请打开蓝牙；如被禁用，可执行 rfkill unblock bluetooth" },
+    DeviceNotFound: { title: "未找到设备", hint: "请唤醒手环、靠近电脑，并确认已开启心率广播" },
+    ServiceMissing: { title: "设备不支持心率服务", hint: "请在手环上开启心率广播后重试" },
+    SubscribeTimeout: { title: "订阅心率数据超时", hint: "请在系统蓝牙设置中移除配对后重新连接" },
+    PermissionDenied: { title: "没有蓝牙权限", hint: "请授予应用蓝牙权限；Linux 下可将当前用户加入 bluetooth 组" },
+    SettingsIo: { title: "读写设置失败", hint: "请确认配置目录存在且可写" },
+    StorageIo: { title: "读写文件失败", hint: "请确认文件存在且配置目录可写" },
+    ConnectionFailed: { title: "连接设备失败", hint: "请确认手环未被其他手机或应用占用后重试" },
+    ConnectionLost: { title: "连接已断开", hint: "请让手环保持在有效范围内" },
+    InvalidArgument: { title: "参数无效", hint: "请检查输入后重试" },
+    InvalidState: { title: "操作冲突", hint: "请等待当前操作完成或先停止" },
+    Bluetooth: { title: "蓝牙错误", hint: "请关闭并重新打开蓝牙后重试" },
+};
+
+// 将后端返回的错误转换为可显示的文本
+const formatError = (error) => {
+    if (!error || typeof error !== "object" || !error.code) {
+        return String(error);
+    }
+    const localized = ERROR_MESSAGES[error.code];
+    if (!localized) {
+        return `${error.message}（${error.hint}）`;
+    }
+    return `${localized.title}。${localized.hint}`;
+};
+
+export { formatError };